@@ -3,19 +3,40 @@
 //! An "accessor" is the entire collection data needed to select *one* "property"
 //! in a bitstream. This might include things like "tile coordinate" or "LUT index".
 
-use core::mem::{self, MaybeUninit};
-
 #[cfg(feature = "alloc")]
 extern crate alloc;
 #[cfg(feature = "alloc")]
 use alloc::borrow::Cow;
 
-use crate::bit_access::{BitArray, Coordinate};
+use core::fmt::Display;
+
+use crate::bit_access::{BitArray, BitRun, BoundedBitArray, Coordinate, OutOfBoundsError};
+use crate::property::{PropertyLeaf, PropertyLeafWithDefault, UnrecognizedBitsError};
 #[cfg(feature = "alloc")]
-use crate::property::PropertyLeafWithStringConv;
-use crate::property::{PropertyLeaf, PropertyLeafWithDefault};
+use crate::property::{PropertyLeafWithStringConv, StringConvError};
 use crate::workarounds::MustBeABoolArrayConstGenericsWorkaround;
 
+/// Error for reading a property whose replicated bits don't agree with each other
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct InconsistentCopiesError {
+    /// Which bit of the property is inconsistent
+    pub biti: usize,
+    /// Where the first copy of the bit is
+    pub first: Coordinate,
+    /// Where the first copy which disagrees with the first copy is
+    pub mismatch: Coordinate,
+}
+impl Display for InconsistentCopiesError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "bit {} at {} disagrees with its copy at {}",
+            self.biti, self.first, self.mismatch
+        )
+    }
+}
+impl core::error::Error for InconsistentCopiesError {}
+
 /// This trait needs to be implemented on a type holding a complete package of state
 pub trait PropertyAccessor {
     /// Must be a bool array containing the exact number of bits this property takes
@@ -27,23 +48,225 @@ pub trait PropertyAccessor {
     /// Must implement this. Return a coordinate and invert Y/N for each bit of this property.
     fn get_bit_pos(&self, biti: usize) -> (Coordinate, bool);
 
+    /// Optional. Return how many [BitRun]s this property's bits can be described as.
+    ///
+    /// If this is nonzero, the runs returned by [PropertyAccessor::get_bit_run] must cover
+    /// every bit, in order, and must agree with [PropertyAccessor::get_bit_pos].
+    /// This allows the bits to be accessed as whole words if the bitstream supports it.
+    fn num_bit_runs(&self) -> usize {
+        0
+    }
+    /// Optional. Return the `runi`th run of bits, see [PropertyAccessor::num_bit_runs]
+    fn get_bit_run(&self, _runi: usize) -> BitRun {
+        unreachable!()
+    }
+
+    /// Optional. Return how many places the `biti`th bit is stored in.
+    ///
+    /// Some devices store the same bit more than once. [PropertyAccessor::set] writes every copy,
+    /// [PropertyAccessor::get] only reads the first one, and [PropertyAccessor::get_checked]
+    /// additionally verifies that all the copies agree.
+    fn num_bit_copies(&self, _biti: usize) -> usize {
+        1
+    }
+    /// Optional. Return the coordinate and invert Y/N of the `copyi`th copy of the `biti`th bit
+    ///
+    /// Copy 0 must be the same as [PropertyAccessor::get_bit_pos].
+    fn get_bit_copy_pos(&self, biti: usize, copyi: usize) -> (Coordinate, bool) {
+        debug_assert_eq!(copyi, 0);
+        self.get_bit_pos(biti)
+    }
+
     /// Automatically read all the bits, create a bool array, and convert it to a friendly type
     fn get(&self, bitstream: &(impl BitArray + ?Sized)) -> Self::Output {
-        let mut bits: <Self::BoolArray as MustBeABoolArrayConstGenericsWorkaround>::MaybeUninitTy =
-            unsafe { MaybeUninit::uninit().assume_init() };
-        for biti in 0..Self::BoolArray::NBITS {
-            let (c, inv) = self.get_bit_pos(biti);
-            bits.as_mut()[biti].write(bitstream.get(c) ^ inv);
+        Self::Output::from_bits(&self.get_raw_bits(bitstream))
+    }
+    /// Like [PropertyAccessor::get], but returns an error (carrying the raw bits)
+    /// if the bits aren't a recognized value of the property
+    fn try_get_decoded(
+        &self,
+        bitstream: &(impl BitArray + ?Sized),
+    ) -> Result<Self::Output, UnrecognizedBitsError<Self::BoolArray>> {
+        Self::Output::checked_from_bits(&self.get_raw_bits(bitstream))
+    }
+    /// Read all the bits into a bool array without converting them
+    fn get_raw_bits(&self, bitstream: &(impl BitArray + ?Sized)) -> Self::BoolArray {
+        let mut bits = Self::BoolArray::from_fn(|_| false);
+        let num_runs = self.num_bit_runs();
+        if num_runs == 0 {
+            for biti in 0..Self::BoolArray::NBITS {
+                let (c, inv) = self.get_bit_pos(biti);
+                bits.as_mut()[biti] = bitstream.get(c) ^ inv;
+            }
+        } else {
+            let mut biti = 0;
+            for runi in 0..num_runs {
+                let run = self.get_bit_run(runi);
+                for_each_run_chunk(&run, |chunk_off, chunk_len| {
+                    let word = read_run_chunk(bitstream, &run, chunk_off, chunk_len);
+                    for i in 0..chunk_len {
+                        bits.as_mut()[biti] = (word >> i) & 1 != 0;
+                        biti += 1;
+                    }
+                });
+            }
+            assert_eq!(
+                biti,
+                Self::BoolArray::NBITS,
+                "bit runs must cover every bit"
+            );
         }
-        let bits = unsafe { mem::transmute_copy::<_, Self::BoolArray>(&bits) };
-        Self::Output::from_bits(&bits)
+        bits
     }
     /// Automatically convert from a friendly type value to a bool array and writes it to the correct coordinates
     fn set(&self, bitstream: &mut (impl BitArray + ?Sized), val: Self::Output) {
         let bits = val.to_bits();
+        let num_runs = self.num_bit_runs();
+        if num_runs == 0 {
+            for biti in 0..Self::BoolArray::NBITS {
+                let (c, inv) = self.get_bit_pos(biti);
+                bitstream.set(c, bits.as_ref()[biti] ^ inv);
+            }
+        } else {
+            let mut biti = 0;
+            for runi in 0..num_runs {
+                let run = self.get_bit_run(runi);
+                for_each_run_chunk(&run, |chunk_off, chunk_len| {
+                    let mut word = 0;
+                    for i in 0..chunk_len {
+                        if bits.as_ref()[biti] {
+                            word |= 1 << i;
+                        }
+                        biti += 1;
+                    }
+                    write_run_chunk(bitstream, &run, chunk_off, chunk_len, word);
+                });
+            }
+            assert_eq!(
+                biti,
+                Self::BoolArray::NBITS,
+                "bit runs must cover every bit"
+            );
+        }
+        for biti in 0..Self::BoolArray::NBITS {
+            for copyi in 1..self.num_bit_copies(biti) {
+                let (c, inv) = self.get_bit_copy_pos(biti, copyi);
+                bitstream.set(c, bits.as_ref()[biti] ^ inv);
+            }
+        }
+    }
+
+    /// Like [PropertyAccessor::get], but returns an error if the copies of any bit disagree
+    fn get_checked(
+        &self,
+        bitstream: &(impl BitArray + ?Sized),
+    ) -> Result<Self::Output, InconsistentCopiesError> {
         for biti in 0..Self::BoolArray::NBITS {
-            let (c, inv) = self.get_bit_pos(biti);
-            bitstream.set(c, bits.as_ref()[biti] ^ inv);
+            let (first, first_inv) = self.get_bit_copy_pos(biti, 0);
+            let val = bitstream.get(first) ^ first_inv;
+            for copyi in 1..self.num_bit_copies(biti) {
+                let (c, inv) = self.get_bit_copy_pos(biti, copyi);
+                if bitstream.get(c) ^ inv != val {
+                    return Err(InconsistentCopiesError {
+                        biti,
+                        first,
+                        mismatch: c,
+                    });
+                }
+            }
+        }
+        Ok(self.get(bitstream))
+    }
+
+    /// Like [PropertyAccessor::get], but returns an error if any bit is outside of the bitstream
+    fn try_get(
+        &self,
+        bitstream: &(impl BoundedBitArray + ?Sized),
+    ) -> Result<Self::Output, OutOfBoundsError> {
+        self.check_bounds(bitstream)?;
+        Ok(self.get(bitstream))
+    }
+    /// Like [PropertyAccessor::set], but returns an error if any bit is outside of the bitstream
+    ///
+    /// Nothing is written if any bit is out of bounds.
+    fn try_set(
+        &self,
+        bitstream: &mut (impl BoundedBitArray + ?Sized),
+        val: Self::Output,
+    ) -> Result<(), OutOfBoundsError> {
+        self.check_bounds(bitstream)?;
+        self.set(bitstream, val);
+        Ok(())
+    }
+    /// Check that every bit of this property is inside the bitstream
+    fn check_bounds(
+        &self,
+        bitstream: &(impl BoundedBitArray + ?Sized),
+    ) -> Result<(), OutOfBoundsError> {
+        for biti in 0..Self::BoolArray::NBITS {
+            for copyi in 0..self.num_bit_copies(biti) {
+                let (c, _) = self.get_bit_copy_pos(biti, copyi);
+                if !bitstream.in_bounds(c) {
+                    return Err(OutOfBoundsError { coordinate: c });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Split a run into pieces that fit in a `u64`
+fn for_each_run_chunk(run: &BitRun, mut f: impl FnMut(usize, usize)) {
+    let mut off = 0;
+    while off < run.len {
+        let chunk_len = usize::min(run.len - off, 64);
+        f(off, chunk_len);
+        off += chunk_len;
+    }
+}
+
+/// Read (up to 64) bits of a run, using the fast path if possible
+fn read_run_chunk(
+    bitstream: &(impl BitArray + ?Sized),
+    run: &BitRun,
+    chunk_off: usize,
+    chunk_len: usize,
+) -> u64 {
+    let word = bitstream
+        .get_run(run.coordinate(chunk_off), run.dir, chunk_len)
+        .unwrap_or_else(|| {
+            let mut word = 0;
+            for i in 0..chunk_len {
+                if bitstream.get(run.coordinate(chunk_off + i)) {
+                    word |= 1 << i;
+                }
+            }
+            word
+        });
+    if run.invert {
+        !word
+    } else {
+        word
+    }
+}
+
+/// Write (up to 64) bits of a run, using the fast path if possible
+fn write_run_chunk(
+    bitstream: &mut (impl BitArray + ?Sized),
+    run: &BitRun,
+    chunk_off: usize,
+    chunk_len: usize,
+    word: u64,
+) {
+    let word = if run.invert { !word } else { word };
+    let word = if chunk_len == 64 {
+        word
+    } else {
+        word & ((1 << chunk_len) - 1)
+    };
+    if !bitstream.set_run(run.coordinate(chunk_off), run.dir, chunk_len, word) {
+        for i in 0..chunk_len {
+            bitstream.set(run.coordinate(chunk_off + i), (word >> i) & 1 != 0);
         }
     }
 }
@@ -77,9 +300,69 @@ where
         &self,
         bitstream: &mut (impl BitArray + ?Sized),
         val: &str,
-    ) -> Result<(), ()> {
+    ) -> Result<(), StringConvError> {
         let val = Self::Output::from_string(val, self)?;
         self.set(bitstream, val);
         Ok(())
     }
 }
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::bitplane::BitPlane;
+
+    /// Two bits in row 0, where bit 1 is also stored inverted in row 1
+    struct Replicated;
+    impl PropertyAccessor for Replicated {
+        type BoolArray = [bool; 2];
+        type Output = u8;
+
+        fn get_bit_pos(&self, biti: usize) -> (Coordinate, bool) {
+            (Coordinate::new(biti, 0), false)
+        }
+        fn num_bit_copies(&self, biti: usize) -> usize {
+            if biti == 1 {
+                2
+            } else {
+                1
+            }
+        }
+        fn get_bit_copy_pos(&self, biti: usize, copyi: usize) -> (Coordinate, bool) {
+            if copyi == 0 {
+                self.get_bit_pos(biti)
+            } else {
+                (Coordinate::new(biti, copyi), true)
+            }
+        }
+    }
+
+    #[test]
+    fn replicated_bits() {
+        let mut p = BitPlane::new(2, 2);
+        assert_eq!(
+            Replicated.get_checked(&p),
+            Err(InconsistentCopiesError {
+                biti: 1,
+                first: Coordinate::new(1, 0),
+                mismatch: Coordinate::new(1, 1),
+            })
+        );
+
+        Replicated.set(&mut p, 0b10);
+        assert!(p.get(Coordinate::new(1, 0)));
+        assert!(!p.get(Coordinate::new(1, 1)));
+        assert_eq!(Replicated.get_checked(&p), Ok(0b10));
+
+        Replicated.set(&mut p, 0b01);
+        assert!(p.get(Coordinate::new(1, 1)));
+        assert_eq!(Replicated.get_checked(&p), Ok(0b01));
+
+        assert_eq!(
+            Replicated.check_bounds(&BitPlane::new(2, 1)),
+            Err(OutOfBoundsError {
+                coordinate: Coordinate::new(1, 1)
+            })
+        );
+    }
+}
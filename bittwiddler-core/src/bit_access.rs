@@ -3,7 +3,7 @@
 
 use core::{
     fmt::Display,
-    ops::{Add, AddAssign, Sub, SubAssign},
+    ops::{Add, AddAssign, Neg, Sub, SubAssign},
 };
 
 /// An `(x, y)` coordinate
@@ -17,38 +17,162 @@ use core::{
 ///  \/
 ///  +y
 /// ```
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Hash)]
+///
+/// Devices with several separate configuration memories can also use `plane`
+/// to select which memory the bit is in. Devices with only one memory can ignore it,
+/// in which case it is always 0. `plane` is an index rather than a third axis,
+/// so arithmetic only applies to `x` and `y`: the result is always in the left-hand side's plane,
+/// and the right-hand side's plane is ignored. Use [Coordinate::with_plane] to move between planes.
+///
+/// Note that struct literals must list `plane` as well (`Coordinate { plane: 0, x, y }`),
+/// so prefer [Coordinate::new] or [Coordinate::new_in_plane].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Hash)]
 pub struct Coordinate {
+    pub plane: usize,
     pub x: usize,
     pub y: usize,
 }
 impl Coordinate {
     pub const fn new(x: usize, y: usize) -> Self {
-        Self { x, y }
+        Self { plane: 0, x, y }
+    }
+
+    pub const fn new_in_plane(x: usize, y: usize, plane: usize) -> Self {
+        Self { plane, x, y }
     }
 
+    /// The same `(x, y)` but in a different plane
+    pub const fn with_plane(self, plane: usize) -> Self {
+        Self { plane, ..self }
+    }
+
+    /// Moves left by `rhs.x` and down by `rhs.y`
+    ///
+    /// The plane is `self`'s, and `rhs.plane` is ignored.
     pub const fn sub_x_add_y(self, rhs: Self) -> Self {
         Self {
+            plane: self.plane,
             x: self.x - rhs.x,
             y: self.y + rhs.y,
         }
     }
 
+    /// Moves right by `rhs.x` and up by `rhs.y`
+    ///
+    /// The plane is `self`'s, and `rhs.plane` is ignored.
     pub const fn add_x_sub_y(self, rhs: Self) -> Self {
         Self {
+            plane: self.plane,
             x: self.x + rhs.x,
             y: self.y - rhs.y,
         }
     }
+
+    /// Like `+`, but returns `None` instead of overflowing
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        match (self.x.checked_add(rhs.x), self.y.checked_add(rhs.y)) {
+            (Some(x), Some(y)) => Some(Self {
+                plane: self.plane,
+                x,
+                y,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Like `-`, but returns `None` instead of going below 0
+    pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match (self.x.checked_sub(rhs.x), self.y.checked_sub(rhs.y)) {
+            (Some(x), Some(y)) => Some(Self {
+                plane: self.plane,
+                x,
+                y,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Like [Coordinate::sub_x_add_y], but returns `None` instead of overflowing
+    pub const fn checked_sub_x_add_y(self, rhs: Self) -> Option<Self> {
+        match (self.x.checked_sub(rhs.x), self.y.checked_add(rhs.y)) {
+            (Some(x), Some(y)) => Some(Self {
+                plane: self.plane,
+                x,
+                y,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Like [Coordinate::add_x_sub_y], but returns `None` instead of overflowing
+    pub const fn checked_add_x_sub_y(self, rhs: Self) -> Option<Self> {
+        match (self.x.checked_add(rhs.x), self.y.checked_sub(rhs.y)) {
+            (Some(x), Some(y)) => Some(Self {
+                plane: self.plane,
+                x,
+                y,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Move by a signed offset, returning `None` if the result is not a valid coordinate
+    ///
+    /// The plane is unchanged.
+    pub const fn checked_offset(self, offset: CoordinateOffset) -> Option<Self> {
+        match (
+            self.x.checked_add_signed(offset.dx),
+            self.y.checked_add_signed(offset.dy),
+        ) {
+            (Some(x), Some(y)) => Some(Self {
+                plane: self.plane,
+                x,
+                y,
+            }),
+            _ => None,
+        }
+    }
+
+    /// The offset which moves `self` to `other`, ignoring planes
+    ///
+    /// Returns `None` if the distance does not fit in an `isize`.
+    pub const fn offset_to(self, other: Self) -> Option<CoordinateOffset> {
+        match (
+            signed_distance(self.x, other.x),
+            signed_distance(self.y, other.y),
+        ) {
+            (Some(dx), Some(dy)) => Some(CoordinateOffset { dx, dy }),
+            _ => None,
+        }
+    }
+}
+/// `to - from`, if it fits in an `isize`
+const fn signed_distance(from: usize, to: usize) -> Option<isize> {
+    if to >= from {
+        let d = to - from;
+        if d <= isize::MAX as usize {
+            Some(d as isize)
+        } else {
+            None
+        }
+    } else {
+        let d = from - to;
+        // isize::MIN has no positive counterpart, but is still representable
+        if d <= isize::MAX as usize {
+            Some(-(d as isize))
+        } else if d == isize::MIN.unsigned_abs() {
+            Some(isize::MIN)
+        } else {
+            None
+        }
+    }
 }
 impl From<(usize, usize)> for Coordinate {
     fn from(value: (usize, usize)) -> Self {
-        Self {
-            x: value.0,
-            y: value.1,
-        }
+        Self::new(value.0, value.1)
     }
 }
+/// Discards the plane
 impl From<Coordinate> for (usize, usize) {
     fn from(value: Coordinate) -> Self {
         (value.x, value.y)
@@ -56,7 +180,11 @@ impl From<Coordinate> for (usize, usize) {
 }
 impl Display for Coordinate {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "({}, {})", self.x, self.y)
+        if self.plane == 0 {
+            write!(f, "({}, {})", self.x, self.y)
+        } else {
+            write!(f, "({}, {}) in plane {}", self.x, self.y, self.plane)
+        }
     }
 }
 impl Add for Coordinate {
@@ -64,6 +192,7 @@ impl Add for Coordinate {
 
     fn add(self, rhs: Self) -> Self::Output {
         Self {
+            plane: self.plane,
             x: self.x + rhs.x,
             y: self.y + rhs.y,
         }
@@ -80,6 +209,7 @@ impl Sub for Coordinate {
 
     fn sub(self, rhs: Self) -> Self::Output {
         Self {
+            plane: self.plane,
             x: self.x - rhs.x,
             y: self.y - rhs.y,
         }
@@ -92,10 +222,296 @@ impl SubAssign for Coordinate {
     }
 }
 
+/// A signed `(dx, dy)` distance between two [Coordinate]s
+///
+/// Unlike [Coordinate], this can point up or left.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Hash)]
+pub struct CoordinateOffset {
+    pub dx: isize,
+    pub dy: isize,
+}
+impl CoordinateOffset {
+    pub const fn new(dx: isize, dy: isize) -> Self {
+        Self { dx, dy }
+    }
+}
+impl From<(isize, isize)> for CoordinateOffset {
+    fn from(value: (isize, isize)) -> Self {
+        Self::new(value.0, value.1)
+    }
+}
+impl Display for CoordinateOffset {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "({:+}, {:+})", self.dx, self.dy)
+    }
+}
+impl Add for CoordinateOffset {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            dx: self.dx + rhs.dx,
+            dy: self.dy + rhs.dy,
+        }
+    }
+}
+impl Sub for CoordinateOffset {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self {
+            dx: self.dx - rhs.dx,
+            dy: self.dy - rhs.dy,
+        }
+    }
+}
+impl Neg for CoordinateOffset {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            dx: -self.dx,
+            dy: -self.dy,
+        }
+    }
+}
+
+/// A `width` x `height` rectangle whose upper-left corner is at `origin`
+///
+/// This is e.g. the region covered by one tile. It only covers bits in `origin`'s plane.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Hash)]
+pub struct Rect {
+    pub origin: Coordinate,
+    pub width: usize,
+    pub height: usize,
+}
+impl Rect {
+    pub const fn new(origin: Coordinate, width: usize, height: usize) -> Self {
+        Self {
+            origin,
+            width,
+            height,
+        }
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub const fn contains(&self, c: Coordinate) -> bool {
+        c.plane == self.origin.plane
+            && c.x >= self.origin.x
+            && c.y >= self.origin.y
+            && c.x - self.origin.x < self.width
+            && c.y - self.origin.y < self.height
+    }
+
+    /// Convert a coordinate inside this rectangle to be relative to `origin`
+    ///
+    /// The result stays in `c`'s (and therefore `origin`'s) plane.
+    /// Returns `None` if `c` is outside of this rectangle.
+    pub const fn to_local(&self, c: Coordinate) -> Option<Coordinate> {
+        if self.contains(c) {
+            Some(Coordinate::new_in_plane(
+                c.x - self.origin.x,
+                c.y - self.origin.y,
+                c.plane,
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Convert a coordinate relative to `origin` to an absolute coordinate
+    ///
+    /// Returns `None` if `local` is outside of this rectangle.
+    pub const fn to_global(&self, local: Coordinate) -> Option<Coordinate> {
+        if local.x < self.width && local.y < self.height {
+            Some(Coordinate::new_in_plane(
+                self.origin.x + local.x,
+                self.origin.y + local.y,
+                self.origin.plane,
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Every coordinate inside this rectangle, in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = Coordinate> {
+        let r = *self;
+        (0..r.height).flat_map(move |y| {
+            (0..r.width).map(move |x| {
+                Coordinate::new_in_plane(r.origin.x + x, r.origin.y + y, r.origin.plane)
+            })
+        })
+    }
+}
+
+/// Direction in which a [BitRun] extends
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum RunDirection {
+    /// Towards +x
+    Right,
+    /// Towards +y
+    Down,
+}
+
+/// A run of `len` adjacent bits starting at `start`
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct BitRun {
+    pub start: Coordinate,
+    pub dir: RunDirection,
+    pub len: usize,
+    pub invert: bool,
+}
+impl BitRun {
+    /// Coordinate of the `i`th bit of this run
+    pub const fn coordinate(&self, i: usize) -> Coordinate {
+        match self.dir {
+            RunDirection::Right => {
+                Coordinate::new_in_plane(self.start.x + i, self.start.y, self.start.plane)
+            }
+            RunDirection::Down => {
+                Coordinate::new_in_plane(self.start.x, self.start.y + i, self.start.plane)
+            }
+        }
+    }
+}
+
 /// Trait that should be implemented on "the struct containing the bitstream's actual data".
 pub trait BitArray {
     fn get(&self, c: Coordinate) -> bool;
     fn set(&mut self, c: Coordinate, val: bool);
+
+    /// Optional fast path for reading up to 64 adjacent bits at once
+    ///
+    /// The bit at `start` is returned in the LSB. Return `None` if this isn't supported
+    /// for the given run, in which case [BitArray::get] will be used for each bit instead.
+    fn get_run(&self, _start: Coordinate, _dir: RunDirection, _len: usize) -> Option<u64> {
+        None
+    }
+    /// Optional fast path for writing up to 64 adjacent bits at once
+    ///
+    /// Return `false` without writing anything if this isn't supported for the given run,
+    /// in which case [BitArray::set] will be used for each bit instead.
+    fn set_run(&mut self, _start: Coordinate, _dir: RunDirection, _len: usize, _bits: u64) -> bool {
+        false
+    }
+}
+impl<T: BitArray + ?Sized> BitArray for &mut T {
+    fn get(&self, c: Coordinate) -> bool {
+        T::get(self, c)
+    }
+    fn set(&mut self, c: Coordinate, val: bool) {
+        T::set(self, c, val)
+    }
+    fn get_run(&self, start: Coordinate, dir: RunDirection, len: usize) -> Option<u64> {
+        T::get_run(self, start, dir, len)
+    }
+    fn set_run(&mut self, start: Coordinate, dir: RunDirection, len: usize, bits: u64) -> bool {
+        T::set_run(self, start, dir, len, bits)
+    }
+}
+
+/// Error for accessing a [Coordinate] that isn't inside a [BoundedBitArray]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct OutOfBoundsError {
+    pub coordinate: Coordinate,
+}
+impl Display for OutOfBoundsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "coordinate {} is out of bounds", self.coordinate)
+    }
+}
+impl core::error::Error for OutOfBoundsError {}
+
+/// Trait for a [BitArray] which knows its own dimensions
+///
+/// This allows accessing bits without panicking if an accessor computes a bad coordinate.
+pub trait BoundedBitArray: BitArray {
+    /// Width of plane 0
+    fn width(&self) -> usize;
+    /// Height of plane 0
+    fn height(&self) -> usize;
+
+    /// Override this if there is more than one plane
+    fn num_planes(&self) -> usize {
+        1
+    }
+    /// Override this if there is more than one plane
+    fn plane_width(&self, plane: usize) -> usize {
+        if plane == 0 {
+            self.width()
+        } else {
+            0
+        }
+    }
+    /// Override this if there is more than one plane
+    fn plane_height(&self, plane: usize) -> usize {
+        if plane == 0 {
+            self.height()
+        } else {
+            0
+        }
+    }
+
+    /// Can be overridden if not every coordinate within the plane's width and height is valid
+    fn in_bounds(&self, c: Coordinate) -> bool {
+        c.plane < self.num_planes()
+            && c.x < self.plane_width(c.plane)
+            && c.y < self.plane_height(c.plane)
+    }
+
+    /// Every valid coordinate in every plane, in row-major order
+    fn all_coordinates(&self) -> impl Iterator<Item = Coordinate> + '_
+    where
+        Self: Sized,
+    {
+        (0..self.num_planes()).flat_map(move |plane| {
+            (0..self.plane_height(plane)).flat_map(move |y| {
+                (0..self.plane_width(plane))
+                    .map(move |x| Coordinate::new_in_plane(x, y, plane))
+                    .filter(move |c| self.in_bounds(*c))
+            })
+        })
+    }
+
+    fn try_get(&self, c: Coordinate) -> Result<bool, OutOfBoundsError> {
+        if self.in_bounds(c) {
+            Ok(self.get(c))
+        } else {
+            Err(OutOfBoundsError { coordinate: c })
+        }
+    }
+    fn try_set(&mut self, c: Coordinate, val: bool) -> Result<(), OutOfBoundsError> {
+        if self.in_bounds(c) {
+            self.set(c, val);
+            Ok(())
+        } else {
+            Err(OutOfBoundsError { coordinate: c })
+        }
+    }
+}
+impl<T: BoundedBitArray + ?Sized> BoundedBitArray for &mut T {
+    fn width(&self) -> usize {
+        T::width(self)
+    }
+    fn height(&self) -> usize {
+        T::height(self)
+    }
+    fn num_planes(&self) -> usize {
+        T::num_planes(self)
+    }
+    fn plane_width(&self, plane: usize) -> usize {
+        T::plane_width(self, plane)
+    }
+    fn plane_height(&self, plane: usize) -> usize {
+        T::plane_height(self, plane)
+    }
+    fn in_bounds(&self, c: Coordinate) -> bool {
+        T::in_bounds(self, c)
+    }
 }
 
 #[cfg(test)]
@@ -114,5 +530,147 @@ mod tests {
         assert_eq!(c1 - c2, Coordinate::new(1, 2));
         c1 -= c2;
         assert_eq!(c1, Coordinate::new(1, 2));
+
+        let origin = Coordinate::new_in_plane(8, 0, 2);
+        assert_eq!(origin + c1, Coordinate::new_in_plane(9, 2, 2));
+        assert_eq!(c1 + origin - origin, c1);
+        assert_eq!(origin.with_plane(0), Coordinate::new(8, 0));
+    }
+
+    #[test]
+    fn coordinate_checked_ops() {
+        let c = Coordinate::new(1, 2);
+        assert_eq!(c.checked_sub(Coordinate::new(2, 0)), None);
+        assert_eq!(
+            c.checked_sub(Coordinate::new(1, 1)),
+            Some(Coordinate::new(0, 1))
+        );
+        assert_eq!(c.checked_add(Coordinate::new(usize::MAX, 0)), None);
+        assert_eq!(c.checked_sub_x_add_y(Coordinate::new(2, 1)), None);
+        assert_eq!(
+            c.checked_add_x_sub_y(Coordinate::new(2, 1)),
+            Some(Coordinate::new(3, 1))
+        );
+
+        let off = CoordinateOffset::new(-1, 3);
+        assert_eq!(c.checked_offset(off), Some(Coordinate::new(0, 5)));
+        assert_eq!(c.checked_offset(off + off), None);
+        assert_eq!(c.offset_to(Coordinate::new(0, 5)), Some(off));
+        assert_eq!(
+            Coordinate::new_in_plane(1, 5, 3).checked_offset(-off),
+            Some(Coordinate::new_in_plane(2, 2, 3))
+        );
+
+        let far = Coordinate::new(usize::MAX, 0);
+        assert_eq!(Coordinate::new(0, 0).offset_to(far), None);
+        assert_eq!(far.offset_to(Coordinate::new(0, 0)), None);
+        assert_eq!(
+            Coordinate::new(isize::MAX as usize + 1, 0).offset_to(Coordinate::new(0, 0)),
+            Some(CoordinateOffset::new(isize::MIN, 0))
+        );
+        assert_eq!(
+            far.checked_sub_x_add_y(Coordinate::new(usize::MAX, 1)),
+            Some(Coordinate::new(0, 1))
+        );
+    }
+
+    #[test]
+    fn coordinate_mixed_planes() {
+        let c = Coordinate::new_in_plane(4, 4, 2);
+        let rhs = Coordinate::new_in_plane(1, 1, 1);
+
+        assert_eq!(c.sub_x_add_y(rhs), Coordinate::new_in_plane(3, 5, 2));
+        assert_eq!(c.add_x_sub_y(rhs), Coordinate::new_in_plane(5, 3, 2));
+        assert_eq!(c.checked_sub_x_add_y(rhs), Some(c.sub_x_add_y(rhs)));
+        assert_eq!(c.checked_add_x_sub_y(rhs), Some(c.add_x_sub_y(rhs)));
+
+        // the plane is an index, so it is never summed
+        assert_eq!(c + c, Coordinate::new_in_plane(8, 8, 2));
+        assert_eq!(c + rhs, Coordinate::new_in_plane(5, 5, 2));
+        assert_eq!(c - rhs, Coordinate::new_in_plane(3, 3, 2));
+        assert_eq!(c.checked_add(rhs), Some(c + rhs));
+        assert_eq!(c.checked_sub(rhs), Some(c - rhs));
+        let mut c2 = c;
+        c2 += rhs;
+        c2 -= rhs;
+        assert_eq!(c2, c);
+        assert_eq!(
+            c.offset_to(Coordinate::new(5, 3)),
+            Some(CoordinateOffset::new(1, -1))
+        );
+    }
+
+    #[test]
+    fn rect() {
+        let r = Rect::new(Coordinate::new(4, 8), 2, 3);
+        assert!(r.contains(Coordinate::new(5, 10)));
+        assert!(!r.contains(Coordinate::new(3, 10)));
+        assert!(!r.contains(Coordinate::new(5, 11)));
+        assert!(!r.contains(Coordinate::new_in_plane(5, 10, 1)));
+        assert_eq!(
+            r.to_local(Coordinate::new(5, 9)),
+            Some(Coordinate::new(1, 1))
+        );
+        assert_eq!(r.to_local(Coordinate::new(6, 9)), None);
+        assert_eq!(
+            r.to_global(Coordinate::new(1, 2)),
+            Some(Coordinate::new(5, 10))
+        );
+        assert_eq!(r.to_global(Coordinate::new(2, 0)), None);
+
+        let r1 = Rect::new(Coordinate::new_in_plane(4, 8, 1), 2, 3);
+        assert_eq!(
+            r1.to_local(Coordinate::new_in_plane(5, 9, 1)),
+            Some(Coordinate::new_in_plane(1, 1, 1))
+        );
+        assert_eq!(r1.to_local(Coordinate::new(5, 9)), None);
+        assert_eq!(r.iter().count(), 6);
+        assert!(r.iter().all(|c| r.contains(c)));
+        assert_eq!(r.iter().nth(2), Some(Coordinate::new(4, 9)));
+    }
+
+    struct TwoByTwo([bool; 4]);
+    impl BitArray for TwoByTwo {
+        fn get(&self, c: Coordinate) -> bool {
+            self.0[c.y * 2 + c.x]
+        }
+        fn set(&mut self, c: Coordinate, val: bool) {
+            self.0[c.y * 2 + c.x] = val;
+        }
+    }
+    impl BoundedBitArray for TwoByTwo {
+        fn width(&self) -> usize {
+            2
+        }
+        fn height(&self) -> usize {
+            2
+        }
+    }
+
+    #[test]
+    fn bounded_access() {
+        let mut b = TwoByTwo([false; 4]);
+        assert_eq!(b.try_set(Coordinate::new(1, 1), true), Ok(()));
+        assert_eq!(b.try_get(Coordinate::new(1, 1)), Ok(true));
+        assert_eq!(
+            b.try_get(Coordinate::new(2, 0)),
+            Err(OutOfBoundsError {
+                coordinate: Coordinate::new(2, 0)
+            })
+        );
+        assert_eq!(
+            b.try_set(Coordinate::new(0, 2), true),
+            Err(OutOfBoundsError {
+                coordinate: Coordinate::new(0, 2)
+            })
+        );
+        assert!(b.try_get(Coordinate::new_in_plane(0, 0, 1)).is_err());
+        assert_eq!(b.0, [false, false, false, true]);
+        assert!(b.all_coordinates().eq([
+            Coordinate::new(0, 0),
+            Coordinate::new(1, 0),
+            Coordinate::new(0, 1),
+            Coordinate::new(1, 1)
+        ]));
     }
 }
@@ -0,0 +1,492 @@
+//! Owned storage types for "a 2-D plane of booleans"
+//!
+//! These are intended to be the default backing store for a bitstream struct,
+//! so that device crates don't each need to hand-roll a [BitArray] impl.
+
+extern crate alloc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::bit_access::{BitArray, BoundedBitArray, Coordinate, RunDirection};
+
+/// Order in which bits are assigned linear indices
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Hash)]
+pub enum Layout {
+    /// Bits `(0, 0)`, `(1, 0)`, `(2, 0)`, ... are adjacent
+    #[default]
+    RowMajor,
+    /// Bits `(0, 0)`, `(0, 1)`, `(0, 2)`, ... are adjacent
+    ColumnMajor,
+}
+impl Layout {
+    fn linear_idx(self, c: Coordinate, width: usize, height: usize) -> usize {
+        assert!(
+            c.plane == 0 && c.x < width && c.y < height,
+            "coordinate {} out of bounds for {}x{} plane",
+            c,
+            width,
+            height
+        );
+        match self {
+            Layout::RowMajor => c.y * width + c.x,
+            Layout::ColumnMajor => c.x * height + c.y,
+        }
+    }
+}
+
+type Word = u64;
+const WORD_BITS: usize = Word::BITS as usize;
+
+/// A packed 2-D array of bits stored in machine words
+///
+/// Bit `n` (in the order given by [Layout]) is stored in bit `n % 64` of word `n / 64`.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct BitPlane {
+    width: usize,
+    height: usize,
+    layout: Layout,
+    words: Vec<Word>,
+}
+impl BitPlane {
+    /// Create a new row-major plane with all bits cleared
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::new_with_layout(width, height, Layout::RowMajor)
+    }
+
+    /// Create a new plane with all bits cleared
+    pub fn new_with_layout(width: usize, height: usize, layout: Layout) -> Self {
+        let nbits = width * height;
+        Self {
+            width,
+            height,
+            layout,
+            words: vec![0; nbits.div_ceil(WORD_BITS)],
+        }
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Raw backing words. Unused bits in the last word are always zero.
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Set every bit to `val`
+    pub fn fill(&mut self, val: bool) {
+        let fill = if val { Word::MAX } else { 0 };
+        for w in &mut self.words {
+            *w = fill;
+        }
+        let tail_bits = (self.width * self.height) % WORD_BITS;
+        if val && tail_bits != 0 {
+            let last = self.words.len() - 1;
+            self.words[last] = (1 << tail_bits) - 1;
+        }
+    }
+
+    /// Number of bits which are set
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// If a run is stored contiguously, return the linear index of its first bit
+    fn contiguous_run_start(
+        &self,
+        start: Coordinate,
+        dir: RunDirection,
+        len: usize,
+    ) -> Option<usize> {
+        let contiguous = match (self.layout, dir) {
+            (Layout::RowMajor, RunDirection::Right) => start.x + len <= self.width,
+            (Layout::ColumnMajor, RunDirection::Down) => start.y + len <= self.height,
+            _ => false,
+        };
+        if !contiguous || len == 0 || len > WORD_BITS {
+            return None;
+        }
+        Some(self.layout.linear_idx(start, self.width, self.height))
+    }
+}
+impl BitArray for BitPlane {
+    fn get(&self, c: Coordinate) -> bool {
+        let n = self.layout.linear_idx(c, self.width, self.height);
+        (self.words[n / WORD_BITS] >> (n % WORD_BITS)) & 1 != 0
+    }
+
+    fn set(&mut self, c: Coordinate, val: bool) {
+        let n = self.layout.linear_idx(c, self.width, self.height);
+        let mask = 1 << (n % WORD_BITS);
+        if val {
+            self.words[n / WORD_BITS] |= mask;
+        } else {
+            self.words[n / WORD_BITS] &= !mask;
+        }
+    }
+
+    fn get_run(&self, start: Coordinate, dir: RunDirection, len: usize) -> Option<u64> {
+        let n = self.contiguous_run_start(start, dir, len)?;
+        let word_i = n / WORD_BITS;
+        let bit_i = n % WORD_BITS;
+
+        let mut bits = self.words[word_i] >> bit_i;
+        if bit_i + len > WORD_BITS {
+            bits |= self.words[word_i + 1] << (WORD_BITS - bit_i);
+        }
+        if len < WORD_BITS {
+            bits &= (1 << len) - 1;
+        }
+        Some(bits)
+    }
+
+    fn set_run(&mut self, start: Coordinate, dir: RunDirection, len: usize, bits: u64) -> bool {
+        let Some(n) = self.contiguous_run_start(start, dir, len) else {
+            return false;
+        };
+        let word_i = n / WORD_BITS;
+        let bit_i = n % WORD_BITS;
+        let mask = if len == WORD_BITS {
+            Word::MAX
+        } else {
+            (1 << len) - 1
+        };
+        let bits = bits & mask;
+
+        self.words[word_i] = (self.words[word_i] & !(mask << bit_i)) | (bits << bit_i);
+        if bit_i + len > WORD_BITS {
+            let shift = WORD_BITS - bit_i;
+            self.words[word_i + 1] = (self.words[word_i + 1] & !(mask >> shift)) | (bits >> shift);
+        }
+        true
+    }
+}
+impl BoundedBitArray for BitPlane {
+    fn width(&self) -> usize {
+        self.width
+    }
+    fn height(&self) -> usize {
+        self.height
+    }
+}
+
+/// Order of bits within one byte
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Hash)]
+pub enum BitOrder {
+    /// The first bit is stored in the least significant bit (`0x01`)
+    #[default]
+    LsbFirst,
+    /// The first bit is stored in the most significant bit (`0x80`)
+    MsbFirst,
+}
+
+/// Order of bytes within one word (see [BytePlaneSettings::word_bytes])
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Hash)]
+pub enum ByteOrder {
+    #[default]
+    LittleEndian,
+    BigEndian,
+}
+
+/// Controls how a [BytePlane] maps coordinates onto bytes
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct BytePlaneSettings {
+    pub layout: Layout,
+    pub bit_order: BitOrder,
+    pub byte_order: ByteOrder,
+    /// Bytes are grouped into words of this many bytes, and [BytePlaneSettings::byte_order]
+    /// applies within each word. This matters for formats that were written out as
+    /// e.g. `u16` or `u32` values.
+    pub word_bytes: usize,
+}
+impl Default for BytePlaneSettings {
+    fn default() -> Self {
+        Self {
+            layout: Layout::RowMajor,
+            bit_order: BitOrder::LsbFirst,
+            byte_order: ByteOrder::LittleEndian,
+            word_bytes: 1,
+        }
+    }
+}
+
+/// A packed 2-D array of bits stored as bytes in a configurable order
+///
+/// This is intended for bitstream formats which are natively a packed sequence of bytes,
+/// so that the bytes can be used as-is without any conversion.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct BytePlane {
+    width: usize,
+    height: usize,
+    settings: BytePlaneSettings,
+    bytes: Vec<u8>,
+}
+impl BytePlane {
+    /// Number of bytes needed to store a plane of the given size
+    pub fn bytes_needed(width: usize, height: usize, settings: &BytePlaneSettings) -> usize {
+        assert!(settings.word_bytes > 0, "word_bytes cannot be zero");
+        let word_bits = settings.word_bytes * 8;
+        (width * height).div_ceil(word_bits) * settings.word_bytes
+    }
+
+    /// Create a new plane with all bits cleared
+    pub fn new(width: usize, height: usize, settings: BytePlaneSettings) -> Self {
+        let nbytes = Self::bytes_needed(width, height, &settings);
+        Self {
+            width,
+            height,
+            settings,
+            bytes: vec![0; nbytes],
+        }
+    }
+
+    /// Wrap existing bytes. Returns `None` if the length doesn't match the size.
+    pub fn from_bytes(
+        width: usize,
+        height: usize,
+        settings: BytePlaneSettings,
+        bytes: Vec<u8>,
+    ) -> Option<Self> {
+        if bytes.len() != Self::bytes_needed(width, height, &settings) {
+            return None;
+        }
+        Some(Self {
+            width,
+            height,
+            settings,
+            bytes,
+        })
+    }
+
+    pub fn settings(&self) -> &BytePlaneSettings {
+        &self.settings
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    fn byte_and_mask(&self, c: Coordinate) -> (usize, u8) {
+        let n = self.settings.layout.linear_idx(c, self.width, self.height);
+        let word_bits = self.settings.word_bytes * 8;
+        let word_i = n / word_bits;
+        let bit_in_word = n % word_bits;
+
+        let byte_in_word = match self.settings.byte_order {
+            ByteOrder::LittleEndian => bit_in_word / 8,
+            ByteOrder::BigEndian => self.settings.word_bytes - 1 - bit_in_word / 8,
+        };
+        let bit_in_byte = match self.settings.bit_order {
+            BitOrder::LsbFirst => bit_in_word % 8,
+            BitOrder::MsbFirst => 7 - bit_in_word % 8,
+        };
+
+        (
+            word_i * self.settings.word_bytes + byte_in_word,
+            1 << bit_in_byte,
+        )
+    }
+}
+impl BitArray for BytePlane {
+    fn get(&self, c: Coordinate) -> bool {
+        let (byte_i, mask) = self.byte_and_mask(c);
+        self.bytes[byte_i] & mask != 0
+    }
+
+    fn set(&mut self, c: Coordinate, val: bool) {
+        let (byte_i, mask) = self.byte_and_mask(c);
+        if val {
+            self.bytes[byte_i] |= mask;
+        } else {
+            self.bytes[byte_i] &= !mask;
+        }
+    }
+}
+impl BoundedBitArray for BytePlane {
+    fn width(&self) -> usize {
+        self.width
+    }
+    fn height(&self) -> usize {
+        self.height
+    }
+}
+
+/// Combines several single-plane [BitArray]s into one multi-plane [BitArray]
+///
+/// Coordinates in plane `n` are passed to `planes[n]` with their plane set to 0.
+/// This allows e.g. modeling separate main and user flash arrays in a single bitstream struct.
+pub struct PlaneSet<B> {
+    pub planes: Vec<B>,
+}
+impl<B: BitArray> PlaneSet<B> {
+    pub fn new(planes: Vec<B>) -> Self {
+        Self { planes }
+    }
+}
+impl<B: BitArray> BitArray for PlaneSet<B> {
+    fn get(&self, c: Coordinate) -> bool {
+        self.planes[c.plane].get(c.with_plane(0))
+    }
+    fn set(&mut self, c: Coordinate, val: bool) {
+        self.planes[c.plane].set(c.with_plane(0), val)
+    }
+    fn get_run(&self, start: Coordinate, dir: RunDirection, len: usize) -> Option<u64> {
+        self.planes[start.plane].get_run(start.with_plane(0), dir, len)
+    }
+    fn set_run(&mut self, start: Coordinate, dir: RunDirection, len: usize, bits: u64) -> bool {
+        self.planes[start.plane].set_run(start.with_plane(0), dir, len, bits)
+    }
+}
+impl<B: BoundedBitArray> BoundedBitArray for PlaneSet<B> {
+    fn width(&self) -> usize {
+        self.plane_width(0)
+    }
+    fn height(&self) -> usize {
+        self.plane_height(0)
+    }
+    fn num_planes(&self) -> usize {
+        self.planes.len()
+    }
+    fn plane_width(&self, plane: usize) -> usize {
+        self.planes.get(plane).map_or(0, |x| x.width())
+    }
+    fn plane_height(&self, plane: usize) -> usize {
+        self.planes.get(plane).map_or(0, |x| x.height())
+    }
+    fn in_bounds(&self, c: Coordinate) -> bool {
+        self.planes
+            .get(c.plane)
+            .is_some_and(|x| x.in_bounds(c.with_plane(0)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bitplane_layouts() {
+        let mut row = BitPlane::new(70, 3);
+        let mut col = BitPlane::new_with_layout(70, 3, Layout::ColumnMajor);
+        assert_eq!(row.words().len(), 4);
+
+        row.set(Coordinate::new(65, 0), true);
+        row.set(Coordinate::new(1, 1), true);
+        assert_eq!(row.words()[1], 0b1000_0010);
+        assert!(row.get(Coordinate::new(65, 0)));
+        assert!(!row.get(Coordinate::new(64, 0)));
+
+        col.set(Coordinate::new(1, 1), true);
+        assert_eq!(col.words()[0], 1 << 4);
+        col.set(Coordinate::new(1, 1), false);
+        assert_eq!(col.count_ones(), 0);
+    }
+
+    #[test]
+    fn bitplane_runs() {
+        let mut p = BitPlane::new(100, 2);
+        assert!(p.set_run(Coordinate::new(60, 0), RunDirection::Right, 8, 0b1010_0101));
+        assert!(p.get(Coordinate::new(60, 0)));
+        assert!(!p.get(Coordinate::new(61, 0)));
+        assert!(p.get(Coordinate::new(67, 0)));
+        assert_eq!(
+            p.get_run(Coordinate::new(60, 0), RunDirection::Right, 8),
+            Some(0b1010_0101)
+        );
+        assert_eq!(
+            p.get_run(Coordinate::new(40, 0), RunDirection::Right, 60),
+            Some(0b1010_0101 << 20)
+        );
+
+        assert!(p.set_run(Coordinate::new(30, 1), RunDirection::Right, 64, u64::MAX));
+        assert_eq!(p.count_ones(), 64 + 4);
+        assert_eq!(
+            p.get_run(Coordinate::new(29, 1), RunDirection::Right, 64)
+                .map(|x| x & 0b11),
+            Some(0b10)
+        );
+
+        // not contiguous
+        assert_eq!(
+            p.get_run(Coordinate::new(0, 0), RunDirection::Down, 2),
+            None
+        );
+        assert_eq!(
+            p.get_run(Coordinate::new(90, 0), RunDirection::Right, 20),
+            None
+        );
+        assert!(!p.set_run(Coordinate::new(0, 0), RunDirection::Down, 2, 0b11));
+    }
+
+    #[test]
+    fn bitplane_fill() {
+        let mut p = BitPlane::new(10, 10);
+        p.fill(true);
+        assert_eq!(p.count_ones(), 100);
+        assert_eq!(p.words()[1], (1 << 36) - 1);
+        p.fill(false);
+        assert_eq!(p.count_ones(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bitplane_out_of_bounds() {
+        let p = BitPlane::new(4, 4);
+        p.get(Coordinate::new(4, 0));
+    }
+
+    #[test]
+    fn bitplane_try_get() {
+        let mut p = BitPlane::new(4, 3);
+        assert_eq!(p.try_set(Coordinate::new(3, 2), true), Ok(()));
+        assert_eq!(p.try_get(Coordinate::new(3, 2)), Ok(true));
+        assert!(p.try_get(Coordinate::new(4, 0)).is_err());
+        assert!(p.try_get(Coordinate::new(0, 3)).is_err());
+    }
+
+    #[test]
+    fn byteplane_orders() {
+        let mut p = BytePlane::new(12, 2, BytePlaneSettings::default());
+        assert_eq!(p.bytes().len(), 3);
+        p.set(Coordinate::new(9, 0), true);
+        assert_eq!(p.bytes(), &[0x00, 0x02, 0x00]);
+
+        let settings = BytePlaneSettings {
+            bit_order: BitOrder::MsbFirst,
+            byte_order: ByteOrder::BigEndian,
+            word_bytes: 2,
+            ..Default::default()
+        };
+        let mut p = BytePlane::new(12, 2, settings);
+        assert_eq!(p.bytes().len(), 4);
+        p.set(Coordinate::new(9, 0), true);
+        p.set(Coordinate::new(0, 0), true);
+        assert_eq!(p.bytes(), &[0x40, 0x80, 0x00, 0x00]);
+        assert!(p.get(Coordinate::new(9, 0)));
+        assert!(!p.get(Coordinate::new(8, 0)));
+
+        let p2 = BytePlane::from_bytes(12, 2, settings, p.clone().into_bytes()).unwrap();
+        assert_eq!(p, p2);
+        assert!(BytePlane::from_bytes(12, 2, settings, alloc::vec![0; 3]).is_none());
+    }
+
+    #[test]
+    fn plane_set() {
+        let mut p = PlaneSet::new(vec![BitPlane::new(4, 4), BitPlane::new(8, 1)]);
+        assert_eq!(p.num_planes(), 2);
+        assert_eq!((p.plane_width(1), p.plane_height(1)), (8, 1));
+        p.set(Coordinate::new_in_plane(7, 0, 1), true);
+        assert!(p.planes[1].get(Coordinate::new(7, 0)));
+        assert!(!p.get(Coordinate::new(3, 0)));
+        assert!(p.try_get(Coordinate::new_in_plane(0, 1, 1)).is_err());
+        assert!(p.try_get(Coordinate::new_in_plane(0, 0, 2)).is_err());
+        assert_eq!(p.all_coordinates().count(), 24);
+        assert_eq!(
+            p.all_coordinates().last(),
+            Some(Coordinate::new_in_plane(7, 0, 1))
+        );
+    }
+}
@@ -0,0 +1,619 @@
+//! Contains property leaves for values which aren't stored as plain binary
+//!
+//! These are newtypes so that the same integer type can be used with different encodings.
+//! Encodings which don't use every bit pattern (such as one-hot) implement
+//! [PropertyLeaf::checked_from_bits], and their [PropertyLeaf::from_bits] panics on invalid patterns.
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
+
+#[cfg(feature = "alloc")]
+use crate::accessor::PropertyAccessor;
+use crate::property::{PropertyLeaf, UnrecognizedBitsError};
+#[cfg(feature = "alloc")]
+use crate::property::{PropertyLeafWithStringConv, StringConvError};
+use crate::workarounds::MustBeABoolArrayConstGenericsWorkaround;
+
+/// Two's complement signed integer, sign-extended from however many bits the property has
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Hash)]
+pub struct Signed<T>(pub T);
+
+/// Unsigned integer stored as a reflected binary Gray code
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Hash)]
+pub struct Gray<T>(pub T);
+
+/// Index of the single bit which is set, or `None` if no bits are set
+///
+/// Having more than one bit set is invalid.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Hash)]
+pub struct OneHot<const N: usize>(pub Option<usize>);
+
+/// Number of bits which are set, where bits must be set starting from bit 0
+///
+/// For example, 3 is stored as `1110` (for a 4-bit property). Gaps are invalid.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Hash)]
+pub struct Thermometer<const N: usize>(pub usize);
+
+/// Stores the wrapped leaf with its bits in the opposite order (i.e. most significant bit first)
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Hash)]
+pub struct MsbFirst<T>(pub T);
+
+/// Value which only means something when an enable bit is set
+///
+/// The enable bit is at index `EN_POS`, and the remaining bits (in order) hold the value.
+/// When disabled, the value bits are written as all zeros and ignored when reading.
+/// This is written as `off` in human-readable files, and it is the default.
+/// Using an `EN_POS` which is not less than the total number of bits is a compile-time error.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub struct Gated<T, const EN_POS: usize>(pub Option<T>);
+impl<T, const EN_POS: usize> Default for Gated<T, EN_POS> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+/// Parse an unsigned integer the same way as the plain integer leaves (decimal, `0x`, or `0b`)
+#[cfg(feature = "alloc")]
+macro_rules! parse_uint {
+    ($s:expr, $uint_ty:ty, $err:expr) => {{
+        let s: &str = $s;
+        if let Some(s) = s.strip_prefix("0x") {
+            <$uint_ty>::from_str_radix(s, 16)
+        } else if let Some(s) = s.strip_prefix("0b") {
+            <$uint_ty>::from_str_radix(s, 2)
+        } else {
+            s.parse::<$uint_ty>()
+        }
+        .map_err(|_| $err())?
+    }};
+}
+
+macro_rules! impl_signed {
+    ($int_ty:ty, $uint_ty:ty; $($nbits:literal)*) => {$(
+        impl PropertyLeaf<[bool; $nbits]> for Signed<$int_ty> {
+            fn from_bits(bits: &[bool; $nbits]) -> Self {
+                let raw = <$uint_ty as PropertyLeaf<[bool; $nbits]>>::from_bits(bits);
+                let shift = <$int_ty>::BITS - $nbits;
+                Self(((raw << shift) as $int_ty) >> shift)
+            }
+
+            fn to_bits(&self) -> [bool; $nbits] {
+                <$uint_ty as PropertyLeaf<[bool; $nbits]>>::to_bits(&(self.0 as $uint_ty))
+            }
+        }
+        #[cfg(feature = "alloc")]
+        impl<A: PropertyAccessor> PropertyLeafWithStringConv<[bool; $nbits], A> for Signed<$int_ty> {
+            fn to_string(&self, _accessor: &A) -> Cow<'static, str> {
+                alloc::format!("{}", self.0).into()
+            }
+            fn from_string(s: &str, _accessor: &A) -> Result<Self, StringConvError> {
+                let err = || StringConvError::new(s).expected(alloc::format!("{}-bit signed integer", $nbits));
+                let val = if let Some(x) = s.strip_prefix('-') {
+                    let magnitude = parse_uint!(x, $uint_ty, err);
+                    if magnitude > <$int_ty>::MIN.unsigned_abs() {
+                        return Err(err());
+                    }
+                    Self((magnitude as $int_ty).wrapping_neg())
+                } else {
+                    let magnitude = parse_uint!(s, $uint_ty, err);
+                    if magnitude > <$int_ty>::MAX as $uint_ty {
+                        return Err(err());
+                    }
+                    Self(magnitude as $int_ty)
+                };
+                // reject values which don't fit in the property
+                if <Self as PropertyLeaf<[bool; $nbits]>>::from_bits(&PropertyLeaf::<[bool; $nbits]>::to_bits(&val)) != val {
+                    return Err(err());
+                }
+                Ok(val)
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_gray {
+    ($uint_ty:ty; $($nbits:literal)*) => {$(
+        impl PropertyLeaf<[bool; $nbits]> for Gray<$uint_ty> {
+            fn from_bits(bits: &[bool; $nbits]) -> Self {
+                let mut val = <$uint_ty as PropertyLeaf<[bool; $nbits]>>::from_bits(bits);
+                let mut shift = 1;
+                while shift < <$uint_ty>::BITS {
+                    val ^= val >> shift;
+                    shift <<= 1;
+                }
+                Self(val)
+            }
+
+            fn to_bits(&self) -> [bool; $nbits] {
+                <$uint_ty as PropertyLeaf<[bool; $nbits]>>::to_bits(&(self.0 ^ (self.0 >> 1)))
+            }
+        }
+        #[cfg(feature = "alloc")]
+        impl<A: PropertyAccessor> PropertyLeafWithStringConv<[bool; $nbits], A> for Gray<$uint_ty> {
+            fn to_string(&self, _accessor: &A) -> Cow<'static, str> {
+                alloc::format!("0x{:X}", self.0).into()
+            }
+            fn from_string(s: &str, _accessor: &A) -> Result<Self, StringConvError> {
+                let err = || StringConvError::new(s).expected(alloc::format!("{}-bit integer", $nbits));
+                let val = Self(parse_uint!(s, $uint_ty, err));
+                // reject values which don't fit in the property
+                if <Self as PropertyLeaf<[bool; $nbits]>>::from_bits(&PropertyLeaf::<[bool; $nbits]>::to_bits(&val)) != val {
+                    return Err(err());
+                }
+                Ok(val)
+            }
+        }
+    )*};
+}
+
+impl_signed!(i8, u8; 1 2 3 4 5 6 7 8);
+impl_signed!(i16, u16; 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16);
+impl_signed!(i32, u32;
+    1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32);
+impl_signed!(i64, u64;
+    1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+    33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61 62 63 64);
+impl_signed!(i128, u128;
+    1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+    33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61 62 63 64
+    65 66 67 68 69 70 71 72 73 74 75 76 77 78 79 80 81 82 83 84 85 86 87 88 89 90 91 92 93 94 95 96
+    97 98 99 100 101 102 103 104 105 106 107 108 109 110 111 112 113 114 115 116 117 118 119 120
+    121 122 123 124 125 126 127 128);
+
+impl_gray!(u8; 1 2 3 4 5 6 7 8);
+impl_gray!(u16; 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16);
+impl_gray!(u32;
+    1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32);
+impl_gray!(u64;
+    1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+    33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61 62 63 64);
+impl_gray!(u128;
+    1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+    33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61 62 63 64
+    65 66 67 68 69 70 71 72 73 74 75 76 77 78 79 80 81 82 83 84 85 86 87 88 89 90 91 92 93 94 95 96
+    97 98 99 100 101 102 103 104 105 106 107 108 109 110 111 112 113 114 115 116 117 118 119 120
+    121 122 123 124 125 126 127 128);
+
+/// Panics if more than one bit is set
+impl<const N: usize> PropertyLeaf<[bool; N]> for OneHot<N> {
+    fn from_bits(bits: &[bool; N]) -> Self {
+        match Self::checked_from_bits(bits) {
+            Ok(x) => x,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Panics if the index is out of range
+    fn to_bits(&self) -> [bool; N] {
+        let mut ret = [false; N];
+        if let Some(i) = self.0 {
+            ret[i] = true;
+        }
+        ret
+    }
+
+    fn checked_from_bits(bits: &[bool; N]) -> Result<Self, UnrecognizedBitsError<[bool; N]>> {
+        let mut ret = None;
+        for (i, &b) in bits.iter().enumerate() {
+            if b {
+                if ret.is_some() {
+                    return Err(UnrecognizedBitsError { bits: *bits });
+                }
+                ret = Some(i);
+            }
+        }
+        Ok(Self(ret))
+    }
+}
+#[cfg(feature = "alloc")]
+impl<const N: usize, A: PropertyAccessor> PropertyLeafWithStringConv<[bool; N], A> for OneHot<N> {
+    fn to_string(&self, _accessor: &A) -> Cow<'static, str> {
+        match self.0 {
+            Some(i) => alloc::format!("{}", i).into(),
+            None => "none".into(),
+        }
+    }
+    fn from_string(s: &str, _accessor: &A) -> Result<Self, StringConvError> {
+        if s == "none" {
+            return Ok(Self(None));
+        }
+        let err = || {
+            StringConvError::new(s)
+                .expected("none")
+                .expected(alloc::format!("index below {}", N))
+        };
+        let i = s.parse::<usize>().map_err(|_| err())?;
+        if i >= N {
+            return Err(err());
+        }
+        Ok(Self(Some(i)))
+    }
+}
+
+/// Panics if the set bits aren't contiguous starting from bit 0
+impl<const N: usize> PropertyLeaf<[bool; N]> for Thermometer<N> {
+    fn from_bits(bits: &[bool; N]) -> Self {
+        match Self::checked_from_bits(bits) {
+            Ok(x) => x,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Panics if the count is more than the number of bits
+    fn to_bits(&self) -> [bool; N] {
+        assert!(self.0 <= N);
+        core::array::from_fn(|i| i < self.0)
+    }
+
+    fn checked_from_bits(bits: &[bool; N]) -> Result<Self, UnrecognizedBitsError<[bool; N]>> {
+        let count = bits.iter().take_while(|b| **b).count();
+        if bits[count..].iter().any(|b| *b) {
+            return Err(UnrecognizedBitsError { bits: *bits });
+        }
+        Ok(Self(count))
+    }
+}
+#[cfg(feature = "alloc")]
+impl<const N: usize, A: PropertyAccessor> PropertyLeafWithStringConv<[bool; N], A>
+    for Thermometer<N>
+{
+    fn to_string(&self, _accessor: &A) -> Cow<'static, str> {
+        alloc::format!("{}", self.0).into()
+    }
+    fn from_string(s: &str, _accessor: &A) -> Result<Self, StringConvError> {
+        let err = || StringConvError::new(s).expected(alloc::format!("count up to {}", N));
+        let count = s.parse::<usize>().map_err(|_| err())?;
+        if count > N {
+            return Err(err());
+        }
+        Ok(Self(count))
+    }
+}
+
+fn reverse_bits<T: MustBeABoolArrayConstGenericsWorkaround>(bits: &T) -> T {
+    T::from_fn(|i| bits.as_ref()[T::NBITS - 1 - i])
+}
+#[allow(private_bounds)]
+impl<T: MustBeABoolArrayConstGenericsWorkaround, L: PropertyLeaf<T>> PropertyLeaf<T>
+    for MsbFirst<L>
+{
+    fn from_bits(bits: &T) -> Self {
+        Self(L::from_bits(&reverse_bits(bits)))
+    }
+
+    fn to_bits(&self) -> T {
+        reverse_bits(&self.0.to_bits())
+    }
+
+    fn checked_from_bits(bits: &T) -> Result<Self, UnrecognizedBitsError<T>> {
+        match L::checked_from_bits(&reverse_bits(bits)) {
+            Ok(x) => Ok(Self(x)),
+            // report the bits in the order they are actually stored
+            Err(_) => Err(UnrecognizedBitsError {
+                bits: T::from_fn(|i| bits.as_ref()[i]),
+            }),
+        }
+    }
+}
+#[cfg(feature = "alloc")]
+#[allow(private_bounds)]
+impl<
+        T: MustBeABoolArrayConstGenericsWorkaround,
+        A: PropertyAccessor + ?Sized,
+        L: PropertyLeafWithStringConv<T, A>,
+    > PropertyLeafWithStringConv<T, A> for MsbFirst<L>
+{
+    fn to_string(&self, accessor: &A) -> Cow<'static, str> {
+        self.0.to_string(accessor)
+    }
+    fn from_string(s: &str, accessor: &A) -> Result<Self, StringConvError> {
+        Ok(Self(L::from_string(s, accessor)?))
+    }
+}
+
+/// Split out the value bits from a [Gated] property, skipping the enable bit
+fn gated_value_bits<
+    T: MustBeABoolArrayConstGenericsWorkaround,
+    V: MustBeABoolArrayConstGenericsWorkaround,
+>(
+    bits: &T,
+    en_pos: usize,
+) -> V {
+    V::from_fn(|i| bits.as_ref()[if i < en_pos { i } else { i + 1 }])
+}
+
+macro_rules! impl_gated {
+    ($($vbits:literal $nbits:literal)*) => {$(
+        impl<T: PropertyLeaf<[bool; $vbits]>, const EN_POS: usize> PropertyLeaf<[bool; $nbits]>
+            for Gated<T, EN_POS>
+        {
+            fn from_bits(bits: &[bool; $nbits]) -> Self {
+                const { assert!(EN_POS < $nbits, "Gated enable bit position is out of range") };
+                if bits[EN_POS] {
+                    Self(Some(T::from_bits(&gated_value_bits(bits, EN_POS))))
+                } else {
+                    Self(None)
+                }
+            }
+
+            fn to_bits(&self) -> [bool; $nbits] {
+                const { assert!(EN_POS < $nbits, "Gated enable bit position is out of range") };
+                let mut ret = [false; $nbits];
+                if let Some(val) = &self.0 {
+                    let val = val.to_bits();
+                    ret[..EN_POS].copy_from_slice(&val[..EN_POS]);
+                    ret[EN_POS] = true;
+                    ret[EN_POS + 1..].copy_from_slice(&val[EN_POS..]);
+                }
+                ret
+            }
+
+            fn checked_from_bits(bits: &[bool; $nbits]) -> Result<Self, UnrecognizedBitsError<[bool; $nbits]>> {
+                const { assert!(EN_POS < $nbits, "Gated enable bit position is out of range") };
+                if bits[EN_POS] {
+                    match T::checked_from_bits(&gated_value_bits(bits, EN_POS)) {
+                        Ok(x) => Ok(Self(Some(x))),
+                        Err(_) => Err(UnrecognizedBitsError { bits: *bits }),
+                    }
+                } else {
+                    Ok(Self(None))
+                }
+            }
+        }
+        #[cfg(feature = "alloc")]
+        impl<A: PropertyAccessor, T: PropertyLeafWithStringConv<[bool; $vbits], A>, const EN_POS: usize>
+            PropertyLeafWithStringConv<[bool; $nbits], A> for Gated<T, EN_POS>
+        {
+            fn to_string(&self, accessor: &A) -> Cow<'static, str> {
+                match &self.0 {
+                    Some(val) => val.to_string(accessor),
+                    None => "off".into(),
+                }
+            }
+            fn from_string(s: &str, accessor: &A) -> Result<Self, StringConvError> {
+                if s == "off" {
+                    Ok(Self(None))
+                } else {
+                    match T::from_string(s, accessor) {
+                        Ok(x) => Ok(Self(Some(x))),
+                        Err(e) => Err(e.expected("off")),
+                    }
+                }
+            }
+        }
+    )*};
+}
+
+impl_gated!(
+    1 2 2 3 3 4 4 5 5 6 6 7 7 8 8 9 9 10 10 11 11 12 12 13 13 14 14 15 15 16 16 17
+    17 18 18 19 19 20 20 21 21 22 22 23 23 24 24 25 25 26 26 27 27 28 28 29 29 30 30 31 31 32 32 33
+    33 34 34 35 35 36 36 37 37 38 38 39 39 40 40 41 41 42 42 43 43 44 44 45 45 46 46 47 47 48 48 49
+    49 50 50 51 51 52 52 53 53 54 54 55 55 56 56 57 57 58 58 59 59 60 60 61 61 62 62 63 63 64 64 65
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signed() {
+        assert_eq!(
+            <Signed<i8> as PropertyLeaf<[bool; 4]>>::from_bits(&[true, false, true, true]),
+            Signed(-3)
+        );
+        assert_eq!(
+            <Signed<i8> as PropertyLeaf<[bool; 4]>>::from_bits(&[true, true, true, false]),
+            Signed(7)
+        );
+        assert_eq!(
+            PropertyLeaf::<[bool; 4]>::to_bits(&Signed(-8i8)),
+            [false, false, false, true]
+        );
+        assert_eq!(
+            <Signed<i8> as PropertyLeaf<[bool; 8]>>::from_bits(&[false; 8]),
+            Signed(0)
+        );
+        assert_eq!(
+            PropertyLeaf::<[bool; 8]>::to_bits(&Signed(-128i8)),
+            [false, false, false, false, false, false, false, true]
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn string_conv() {
+        use crate::bit_access::Coordinate;
+        use crate::property::PropertyLeafWithDefault;
+
+        struct Dummy;
+        impl PropertyAccessor for Dummy {
+            type BoolArray = [bool; 4];
+            type Output = Signed<i8>;
+
+            fn get_bit_pos(&self, biti: usize) -> (Coordinate, bool) {
+                (Coordinate::new(biti, 0), false)
+            }
+        }
+
+        let conv =
+            |s| <Signed<i8> as PropertyLeafWithStringConv<[bool; 4], _>>::from_string(s, &Dummy);
+        assert_eq!(conv("-8"), Ok(Signed(-8)));
+        assert_eq!(conv("7"), Ok(Signed(7)));
+        assert_eq!(conv("-0x2"), Ok(Signed(-2)));
+        assert_eq!(
+            alloc::string::ToString::to_string(&conv("8").unwrap_err()),
+            "invalid value \"8\", expected 4-bit signed integer"
+        );
+        assert_eq!(conv("-9").unwrap_err().text, "-9");
+        assert_eq!(
+            <Signed<i8> as PropertyLeafWithStringConv<[bool; 8], _>>::from_string("-128", &Dummy),
+            Ok(Signed(-128))
+        );
+        assert_eq!(
+            PropertyLeafWithStringConv::<[bool; 4], _>::to_string(&Signed(-3i8), &Dummy),
+            "-3"
+        );
+
+        assert_eq!(
+            <Gray<u8> as PropertyLeafWithStringConv<[bool; 3], _>>::from_string("0x7", &Dummy),
+            Ok(Gray(7))
+        );
+        assert_eq!(
+            <Gray<u8> as PropertyLeafWithStringConv<[bool; 3], _>>::from_string("8", &Dummy),
+            Err(StringConvError::new("8").expected("3-bit integer"))
+        );
+        assert_eq!(
+            <OneHot<4> as PropertyLeafWithStringConv<[bool; 4], _>>::from_string("none", &Dummy),
+            Ok(OneHot(None))
+        );
+        assert_eq!(
+            <OneHot<4> as PropertyLeafWithStringConv<[bool; 4], _>>::from_string("4", &Dummy),
+            Err(StringConvError::new("4")
+                .expected("none")
+                .expected("index below 4"))
+        );
+        assert_eq!(
+            <Thermometer<4> as PropertyLeafWithStringConv<[bool; 4], _>>::from_string("4", &Dummy),
+            Ok(Thermometer(4))
+        );
+        assert_eq!(
+            <MsbFirst<u8> as PropertyLeafWithStringConv<[bool; 4], _>>::from_string("0x3", &Dummy),
+            Ok(MsbFirst(3))
+        );
+
+        type Clk = Gated<u8, 1>;
+        assert_eq!(
+            <Clk as PropertyLeafWithStringConv<[bool; 4], _>>::to_string(&Gated(None), &Dummy),
+            "off"
+        );
+        assert_eq!(
+            <Clk as PropertyLeafWithStringConv<[bool; 4], _>>::to_string(&Gated(Some(5)), &Dummy),
+            "0x5"
+        );
+        assert_eq!(
+            <Clk as PropertyLeafWithStringConv<[bool; 4], _>>::from_string("off", &Dummy),
+            Ok(Gated(None))
+        );
+        assert_eq!(
+            <Clk as PropertyLeafWithStringConv<[bool; 4], _>>::from_string("7", &Dummy),
+            Ok(Gated(Some(7)))
+        );
+        // disabled counts as the default, so it gets left out of human-readable files
+        assert!(PropertyLeafWithDefault::<[bool; 4], Dummy>::is_default(
+            &Clk::default(),
+            &Dummy
+        ));
+        assert!(!PropertyLeafWithDefault::<[bool; 4], Dummy>::is_default(
+            &Gated::<u8, 1>(Some(0)),
+            &Dummy
+        ));
+    }
+
+    #[test]
+    fn gray() {
+        // 0 1 3 2 6 7 5 4
+        let codes = [0b000, 0b001, 0b011, 0b010, 0b110, 0b111, 0b101, 0b100u8];
+        for (i, code) in codes.iter().enumerate() {
+            let bits = PropertyLeaf::<[bool; 3]>::to_bits(code);
+            assert_eq!(PropertyLeaf::<[bool; 3]>::to_bits(&Gray(i as u8)), bits);
+            assert_eq!(
+                <Gray<u8> as PropertyLeaf<[bool; 3]>>::from_bits(&bits),
+                Gray(i as u8)
+            );
+        }
+    }
+
+    #[test]
+    fn one_hot() {
+        assert_eq!(
+            OneHot::<4>::checked_from_bits(&[false, false, true, false]),
+            Ok(OneHot(Some(2)))
+        );
+        assert_eq!(
+            OneHot::<4>::checked_from_bits(&[false; 4]),
+            Ok(OneHot(None))
+        );
+        assert_eq!(
+            OneHot::<4>::checked_from_bits(&[true, false, true, false]),
+            Err(UnrecognizedBitsError {
+                bits: [true, false, true, false]
+            })
+        );
+        assert_eq!(OneHot::<4>(Some(1)).to_bits(), [false, true, false, false]);
+    }
+
+    #[test]
+    fn thermometer() {
+        assert_eq!(
+            Thermometer::<4>::checked_from_bits(&[true, true, true, false]),
+            Ok(Thermometer(3))
+        );
+        assert_eq!(
+            Thermometer::<4>::checked_from_bits(&[true; 4]),
+            Ok(Thermometer(4))
+        );
+        assert!(Thermometer::<4>::checked_from_bits(&[true, false, true, false]).is_err());
+        assert_eq!(Thermometer::<4>(1).to_bits(), [true, false, false, false]);
+    }
+
+    #[test]
+    fn msb_first() {
+        assert_eq!(
+            <MsbFirst<u8> as PropertyLeaf<[bool; 4]>>::from_bits(&[false, false, true, true]),
+            MsbFirst(3)
+        );
+        assert_eq!(
+            PropertyLeaf::<[bool; 4]>::to_bits(&MsbFirst(Signed(-2i8))),
+            [true, true, true, false]
+        );
+        assert_eq!(
+            MsbFirst::<Thermometer<4>>::checked_from_bits(&[false, true, true, true]),
+            Ok(MsbFirst(Thermometer(3)))
+        );
+        assert_eq!(
+            MsbFirst::<OneHot<3>>::checked_from_bits(&[true, true, false]),
+            Err(UnrecognizedBitsError {
+                bits: [true, true, false]
+            })
+        );
+    }
+
+    #[test]
+    fn gated() {
+        type Clk = Gated<u8, 1>;
+        assert_eq!(
+            <Clk as PropertyLeaf<[bool; 4]>>::from_bits(&[true, true, false, true]),
+            Gated(Some(0b101))
+        );
+        // value bits are ignored when disabled
+        assert_eq!(
+            <Clk as PropertyLeaf<[bool; 4]>>::from_bits(&[true, false, true, true]),
+            Gated(None)
+        );
+        assert_eq!(
+            PropertyLeaf::<[bool; 4]>::to_bits(&Clk::default()),
+            [false; 4]
+        );
+        assert_eq!(
+            PropertyLeaf::<[bool; 4]>::to_bits(&Gated::<u8, 1>(Some(0b110))),
+            [false, true, true, true]
+        );
+
+        assert_eq!(
+            Gated::<OneHot<2>, 0>::checked_from_bits(&[true, false, true]),
+            Ok(Gated(Some(OneHot(Some(1)))))
+        );
+        assert_eq!(
+            Gated::<OneHot<2>, 0>::checked_from_bits(&[false, true, true]),
+            Ok(Gated(None))
+        );
+        assert_eq!(
+            Gated::<OneHot<2>, 0>::checked_from_bits(&[true, true, true]),
+            Err(UnrecognizedBitsError {
+                bits: [true, true, true]
+            })
+        );
+    }
+}
@@ -2,16 +2,26 @@
 //!
 //! Most of this is intended to be implemented automatically with macros
 
+use core::any::type_name;
 use core::str::FromStr;
 
 extern crate alloc;
 use alloc::borrow::Cow;
 use alloc::boxed::Box;
+use alloc::format;
 use alloc::string::ToString;
 
 use crate::accessor::{PropertyAccessorWithDefault, PropertyAccessorWithStringConv};
-use crate::bit_access::BitArray;
-use crate::property::{PropertyLeafWithDefault, PropertyLeafWithStringConv};
+use crate::bit_access::{BitArray, Coordinate};
+use crate::property::{
+    PropertyLeaf, PropertyLeafWithDefault, PropertyLeafWithStringConv, StringConvError,
+};
+use crate::schema::LevelSchema;
+use crate::var_width::{
+    VarWidthPropertyAccessorWithDefault, VarWidthPropertyAccessorWithStringConv,
+    VarWidthPropertyLeafWithDefault, VarWidthPropertyLeafWithStringConv,
+};
+use crate::workarounds::MustBeABoolArrayConstGenericsWorkaround;
 
 /// Trait to be implemented by human text *writer* software to accept
 /// "pieces of state" at _this_ hierarchy sublevel only
@@ -25,7 +35,7 @@ pub trait StatePiece {
         true
     }
     fn to_human_string(&self) -> Cow<'static, str>;
-    fn from_human_string(s: &str) -> Result<Self, ()>
+    fn from_human_string(s: &str) -> Result<Self, StringConvError>
     where
         Self: Sized;
 }
@@ -34,11 +44,11 @@ impl<T: ToString + FromStr> StatePiece for T {
         self.to_string().into()
     }
 
-    fn from_human_string(s: &str) -> Result<Self, ()>
+    fn from_human_string(s: &str) -> Result<Self, StringConvError>
     where
         Self: Sized,
     {
-        Self::from_str(s).map_err(|_| ())
+        Self::from_str(s).map_err(|_| StringConvError::new(s).expected(type_name::<T>()))
     }
 }
 
@@ -54,7 +64,27 @@ pub trait HumanLevelThatHasState {
 pub trait PropertyAccessorDyn: HumanLevelThatHasState {
     fn _human_is_at_default(&self, bitstream: &dyn BitArray) -> bool;
     fn _human_string_get(&self, bitstream: &dyn BitArray) -> Cow<'static, str>;
-    fn _human_string_set(&self, bitstream: &mut dyn BitArray, val: &str) -> Result<(), ()>;
+    fn _human_string_set(
+        &self,
+        bitstream: &mut dyn BitArray,
+        val: &str,
+    ) -> Result<(), StringConvError>;
+
+    /// Used by tools which need to know where the bits are (e.g. to check for overlaps)
+    ///
+    /// The default reports no bits, in which case such tools skip this property.
+    fn _human_num_bits(&self) -> usize {
+        0
+    }
+    fn _human_get_bit_pos(&self, biti: usize) -> (Coordinate, bool) {
+        panic!("bit {} of a property without known bit positions", biti)
+    }
+    fn _human_num_bit_copies(&self, _biti: usize) -> usize {
+        1
+    }
+    fn _human_get_bit_copy_pos(&self, biti: usize, _copyi: usize) -> (Coordinate, bool) {
+        self._human_get_bit_pos(biti)
+    }
 }
 impl<A: PropertyAccessorWithStringConv + PropertyAccessorWithDefault + HumanLevelThatHasState>
     PropertyAccessorDyn for Box<A>
@@ -63,15 +93,39 @@ where
         PropertyLeafWithStringConv<A::BoolArray, A> + PropertyLeafWithDefault<A::BoolArray, A>,
 {
     fn _human_is_at_default(&self, bitstream: &dyn BitArray) -> bool {
-        self.is_at_default(bitstream)
+        match A::Output::checked_from_bits(&self.get_raw_bits(bitstream)) {
+            Ok(val) => val.is_default(self),
+            // unrecognized bits are never the default, so that they show up in dumps
+            Err(_) => false,
+        }
     }
     fn _human_string_get(&self, bitstream: &dyn BitArray) -> Cow<'static, str> {
-        self.get_as_string(bitstream)
+        match A::Output::checked_from_bits(&self.get_raw_bits(bitstream)) {
+            Ok(val) => val.to_string(self),
+            Err(e) => format!("<{}>", e).into(),
+        }
     }
 
-    fn _human_string_set(&self, bitstream: &mut dyn BitArray, val: &str) -> Result<(), ()> {
+    fn _human_string_set(
+        &self,
+        bitstream: &mut dyn BitArray,
+        val: &str,
+    ) -> Result<(), StringConvError> {
         self.set_from_string(bitstream, val)
     }
+
+    fn _human_num_bits(&self) -> usize {
+        A::BoolArray::NBITS
+    }
+    fn _human_get_bit_pos(&self, biti: usize) -> (Coordinate, bool) {
+        self.get_bit_pos(biti)
+    }
+    fn _human_num_bit_copies(&self, biti: usize) -> usize {
+        self.num_bit_copies(biti)
+    }
+    fn _human_get_bit_copy_pos(&self, biti: usize, copyi: usize) -> (Coordinate, bool) {
+        self.get_bit_copy_pos(biti, copyi)
+    }
 }
 impl<A: HumanLevelThatHasState> HumanLevelThatHasState for Box<A> {
     fn _human_dump_my_state(&self, dump: &mut dyn HumanSinkForStatePieces) {
@@ -79,8 +133,50 @@ impl<A: HumanLevelThatHasState> HumanLevelThatHasState for Box<A> {
     }
 }
 
+/// Wrapper to poke a runtime-width property leaf node accessor
+///
+/// You shouldn't need to use this, the macros use it for `#[bittwiddler::var_width]` properties.
+pub struct VarWidthDyn<A>(pub A);
+impl<
+        A: VarWidthPropertyAccessorWithStringConv
+            + VarWidthPropertyAccessorWithDefault
+            + HumanLevelThatHasState,
+    > PropertyAccessorDyn for VarWidthDyn<A>
+where
+    A::Output: VarWidthPropertyLeafWithStringConv<A> + VarWidthPropertyLeafWithDefault<A>,
+{
+    fn _human_is_at_default(&self, bitstream: &dyn BitArray) -> bool {
+        self.0.is_at_default(bitstream)
+    }
+    fn _human_string_get(&self, bitstream: &dyn BitArray) -> Cow<'static, str> {
+        self.0.get_as_string(bitstream)
+    }
+
+    fn _human_string_set(
+        &self,
+        bitstream: &mut dyn BitArray,
+        val: &str,
+    ) -> Result<(), StringConvError> {
+        self.0.set_from_string(bitstream, val)
+    }
+
+    fn _human_num_bits(&self) -> usize {
+        self.0.nbits()
+    }
+    fn _human_get_bit_pos(&self, biti: usize) -> (Coordinate, bool) {
+        self.0.get_bit_pos(biti)
+    }
+}
+impl<A: HumanLevelThatHasState> HumanLevelThatHasState for VarWidthDyn<A> {
+    fn _human_dump_my_state(&self, dump: &mut dyn HumanSinkForStatePieces) {
+        self.0._human_dump_my_state(dump);
+    }
+}
+
 /// Trait intended to be implemented automagically for a hierarchy sublevel
 pub trait HumanLevelDynamicAccessor: HumanLevelThatHasState {
+    /// Description of this level, see [HumanLevelWithSchema](crate::schema::HumanLevelWithSchema)
+    fn _human_level_schema(&self) -> &'static LevelSchema;
     fn _human_fields(&self) -> &'static [&'static str];
     fn _human_sublevels(&self) -> &'static [&'static str];
 
@@ -88,7 +184,7 @@ pub trait HumanLevelDynamicAccessor: HumanLevelThatHasState {
         &self,
         idx: usize,
         params: &[&str],
-    ) -> Result<Box<dyn PropertyAccessorDyn>, ()>;
+    ) -> Result<Box<dyn PropertyAccessorDyn>, StringConvError>;
     fn _human_construct_all_fields<'s>(
         &'s self,
         idx: usize,
@@ -98,7 +194,7 @@ pub trait HumanLevelDynamicAccessor: HumanLevelThatHasState {
         &self,
         idx: usize,
         params: &[&str],
-    ) -> Result<Box<dyn HumanLevelDynamicAccessor>, ()>;
+    ) -> Result<Box<dyn HumanLevelDynamicAccessor>, StringConvError>;
     fn _human_construct_all_sublevels<'s>(
         &'s self,
         idx: usize,
@@ -9,6 +9,16 @@ pub mod prelude;
 mod accessor;
 mod bit_access;
 #[cfg(feature = "alloc")]
+mod bitplane;
+mod encoding;
+#[cfg(feature = "alloc")]
 mod human_text;
+mod linear;
 mod property;
+mod schema;
+#[cfg(feature = "alloc")]
+mod tracking;
+#[cfg(feature = "alloc")]
+mod var_width;
+mod view;
 mod workarounds;
@@ -0,0 +1,391 @@
+//! Contains adapters between "a 1-D array of booleans" and [BitArray]
+//!
+//! Some bitstream formats (e.g. JEDEC files) number their bits linearly ("fuse numbers").
+//! These adapters allow tile spreadsheets and accessors, which always work in 2-D,
+//! to be used unchanged on top of such a format (and vice versa).
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::bit_access::{BitArray, BoundedBitArray, Coordinate};
+
+/// Trait for a 1-D array of bits, such as the fuse array of a JEDEC file
+pub trait LinearBitArray {
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn get(&self, idx: usize) -> bool;
+    fn set(&mut self, idx: usize, val: bool);
+}
+impl LinearBitArray for [bool] {
+    fn len(&self) -> usize {
+        <[bool]>::len(self)
+    }
+    fn get(&self, idx: usize) -> bool {
+        self[idx]
+    }
+    fn set(&mut self, idx: usize, val: bool) {
+        self[idx] = val;
+    }
+}
+#[cfg(feature = "alloc")]
+impl LinearBitArray for Vec<bool> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+    fn get(&self, idx: usize) -> bool {
+        self[idx]
+    }
+    fn set(&mut self, idx: usize, val: bool) {
+        self[idx] = val;
+    }
+}
+impl<T: LinearBitArray + ?Sized> LinearBitArray for &mut T {
+    fn len(&self) -> usize {
+        T::len(self)
+    }
+    fn get(&self, idx: usize) -> bool {
+        T::get(self, idx)
+    }
+    fn set(&mut self, idx: usize, val: bool) {
+        T::set(self, idx, val)
+    }
+}
+
+/// Describes how linear bit indices are arranged into a 2-D plane
+pub trait LinearMapping {
+    /// Width of the 2-D plane (the longest row, if rows are not all the same length)
+    fn width(&self) -> usize;
+    /// Height of the 2-D plane
+    fn height(&self) -> usize;
+    /// Total number of linear bits
+    fn linear_len(&self) -> usize;
+
+    /// Returns `None` if there is no linear bit at this coordinate
+    ///
+    /// Linear bits are always in plane 0.
+    fn to_linear(&self, c: Coordinate) -> Option<usize>;
+    /// Returns `None` if this linear bit isn't part of the 2-D plane
+    fn to_coordinate(&self, idx: usize) -> Option<Coordinate>;
+}
+
+/// Every row has the same number of bits, except possibly the last one
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct FixedStride {
+    stride: usize,
+    len: usize,
+}
+impl FixedStride {
+    /// `len` bits in rows of `stride` bits
+    ///
+    /// Returns `None` if `stride` is 0.
+    pub const fn new(stride: usize, len: usize) -> Option<Self> {
+        if stride == 0 {
+            None
+        } else {
+            Some(Self { stride, len })
+        }
+    }
+}
+impl LinearMapping for FixedStride {
+    fn width(&self) -> usize {
+        self.stride
+    }
+    fn height(&self) -> usize {
+        self.len.div_ceil(self.stride)
+    }
+    fn linear_len(&self) -> usize {
+        self.len
+    }
+
+    fn to_linear(&self, c: Coordinate) -> Option<usize> {
+        if c.plane != 0 || c.x >= self.stride {
+            return None;
+        }
+        let idx = c.y.checked_mul(self.stride)?.checked_add(c.x)?;
+        if idx < self.len {
+            Some(idx)
+        } else {
+            None
+        }
+    }
+    fn to_coordinate(&self, idx: usize) -> Option<Coordinate> {
+        if idx < self.len {
+            Some(Coordinate::new(idx % self.stride, idx / self.stride))
+        } else {
+            None
+        }
+    }
+}
+
+/// Row `y` contains `lengths[y]` bits, immediately following the bits of the previous row
+#[cfg(feature = "alloc")]
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct RowLengths {
+    /// Linear index of the first bit of each row, followed by the total number of bits
+    starts: Vec<usize>,
+    width: usize,
+}
+#[cfg(feature = "alloc")]
+impl RowLengths {
+    pub fn new(lengths: &[usize]) -> Self {
+        let mut starts = Vec::with_capacity(lengths.len() + 1);
+        let mut start = 0;
+        starts.push(start);
+        for len in lengths {
+            start += len;
+            starts.push(start);
+        }
+        Self {
+            starts,
+            width: lengths.iter().copied().max().unwrap_or(0),
+        }
+    }
+}
+#[cfg(feature = "alloc")]
+impl LinearMapping for RowLengths {
+    fn width(&self) -> usize {
+        self.width
+    }
+    fn height(&self) -> usize {
+        self.starts.len() - 1
+    }
+    fn linear_len(&self) -> usize {
+        self.starts[self.starts.len() - 1]
+    }
+
+    fn to_linear(&self, c: Coordinate) -> Option<usize> {
+        if c.plane != 0 || c.y >= self.height() {
+            return None;
+        }
+        let idx = self.starts[c.y] + c.x;
+        if idx < self.starts[c.y + 1] {
+            Some(idx)
+        } else {
+            None
+        }
+    }
+    fn to_coordinate(&self, idx: usize) -> Option<Coordinate> {
+        if idx >= self.linear_len() {
+            return None;
+        }
+        // the last row starting at or before idx (skipping any empty rows)
+        let y = self.starts.partition_point(|&start| start <= idx) - 1;
+        Some(Coordinate::new(idx - self.starts[y], y))
+    }
+}
+
+/// Arbitrary mapping, where `table[y * width + x]` is the linear index of `(x, y)`
+#[cfg(feature = "alloc")]
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct IndexTable {
+    width: usize,
+    table: Vec<Option<usize>>,
+    /// Position in `table` of each linear index
+    inverse: Vec<Option<usize>>,
+}
+#[cfg(feature = "alloc")]
+impl IndexTable {
+    /// Returns `None` if `width` is 0 or if a linear index appears more than once
+    pub fn new(width: usize, table: Vec<Option<usize>>) -> Option<Self> {
+        if width == 0 {
+            return None;
+        }
+        let linear_len = table.iter().filter_map(|x| *x).max().map_or(0, |x| x + 1);
+        let mut inverse = alloc::vec![None; linear_len];
+        for (pos, idx) in table.iter().enumerate() {
+            if let Some(idx) = *idx {
+                if inverse[idx].replace(pos).is_some() {
+                    return None;
+                }
+            }
+        }
+        Some(Self {
+            width,
+            table,
+            inverse,
+        })
+    }
+}
+#[cfg(feature = "alloc")]
+impl LinearMapping for IndexTable {
+    fn width(&self) -> usize {
+        self.width
+    }
+    fn height(&self) -> usize {
+        self.table.len().div_ceil(self.width)
+    }
+    fn linear_len(&self) -> usize {
+        self.inverse.len()
+    }
+
+    fn to_linear(&self, c: Coordinate) -> Option<usize> {
+        if c.plane != 0 || c.x >= self.width {
+            return None;
+        }
+        let pos = c.y.checked_mul(self.width)?.checked_add(c.x)?;
+        self.table.get(pos).copied().flatten()
+    }
+    fn to_coordinate(&self, idx: usize) -> Option<Coordinate> {
+        let pos = self.inverse.get(idx).copied().flatten()?;
+        Some(Coordinate::new(pos % self.width, pos / self.width))
+    }
+}
+
+/// Presents a [LinearBitArray] as a 2-D [BitArray]
+///
+/// Accessing a coordinate which doesn't map to a linear bit will panic
+/// (use [BoundedBitArray::try_get] to avoid this).
+pub struct LinearAsBitArray<L, M> {
+    pub linear: L,
+    pub mapping: M,
+}
+impl<L: LinearBitArray, M: LinearMapping> LinearAsBitArray<L, M> {
+    pub fn new(linear: L, mapping: M) -> Self {
+        Self { linear, mapping }
+    }
+
+    fn idx(&self, c: Coordinate) -> usize {
+        self.mapping
+            .to_linear(c)
+            .unwrap_or_else(|| panic!("coordinate {} does not map to a linear bit", c))
+    }
+}
+impl<L: LinearBitArray, M: LinearMapping> BitArray for LinearAsBitArray<L, M> {
+    fn get(&self, c: Coordinate) -> bool {
+        self.linear.get(self.idx(c))
+    }
+    fn set(&mut self, c: Coordinate, val: bool) {
+        let idx = self.idx(c);
+        self.linear.set(idx, val);
+    }
+}
+impl<L: LinearBitArray, M: LinearMapping> BoundedBitArray for LinearAsBitArray<L, M> {
+    fn width(&self) -> usize {
+        self.mapping.width()
+    }
+    fn height(&self) -> usize {
+        self.mapping.height()
+    }
+    fn in_bounds(&self, c: Coordinate) -> bool {
+        self.mapping
+            .to_linear(c)
+            .is_some_and(|idx| idx < self.linear.len())
+    }
+}
+
+/// Presents a 2-D [BitArray] as a [LinearBitArray]
+///
+/// Accessing a linear index which doesn't map to a coordinate will panic.
+pub struct BitArrayAsLinear<B, M> {
+    pub bitarray: B,
+    pub mapping: M,
+}
+impl<B: BitArray, M: LinearMapping> BitArrayAsLinear<B, M> {
+    pub fn new(bitarray: B, mapping: M) -> Self {
+        Self { bitarray, mapping }
+    }
+
+    fn coordinate(&self, idx: usize) -> Coordinate {
+        self.mapping
+            .to_coordinate(idx)
+            .unwrap_or_else(|| panic!("linear bit {} does not map to a coordinate", idx))
+    }
+}
+impl<B: BitArray, M: LinearMapping> LinearBitArray for BitArrayAsLinear<B, M> {
+    fn len(&self) -> usize {
+        self.mapping.linear_len()
+    }
+    fn get(&self, idx: usize) -> bool {
+        self.bitarray.get(self.coordinate(idx))
+    }
+    fn set(&mut self, idx: usize, val: bool) {
+        let c = self.coordinate(idx);
+        self.bitarray.set(c, val);
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn check_round_trip(m: &impl LinearMapping) {
+        for idx in 0..m.linear_len() {
+            if let Some(c) = m.to_coordinate(idx) {
+                assert!(c.x < m.width() && c.y < m.height());
+                assert_eq!(m.to_linear(c), Some(idx));
+            }
+        }
+    }
+
+    #[test]
+    fn fixed_stride() {
+        assert_eq!(FixedStride::new(0, 10), None);
+        let m = FixedStride::new(4, 10).unwrap();
+        check_round_trip(&m);
+        assert_eq!(m.height(), 3);
+        assert_eq!(m.to_linear(Coordinate::new(1, 2)), Some(9));
+        assert_eq!(m.to_linear(Coordinate::new(2, 2)), None);
+        assert_eq!(m.to_linear(Coordinate::new(4, 0)), None);
+        assert_eq!(m.to_coordinate(5), Some(Coordinate::new(1, 1)));
+    }
+
+    #[test]
+    fn row_lengths() {
+        let m = RowLengths::new(&[3, 5, 2]);
+        check_round_trip(&m);
+        assert_eq!(m.width(), 5);
+        assert_eq!(m.linear_len(), 10);
+        assert_eq!(m.to_linear(Coordinate::new(4, 1)), Some(7));
+        assert_eq!(m.to_linear(Coordinate::new(3, 0)), None);
+        assert_eq!(m.to_coordinate(8), Some(Coordinate::new(0, 2)));
+        assert_eq!(m.to_coordinate(10), None);
+
+        let m = RowLengths::new(&[2, 0, 0, 1]);
+        check_round_trip(&m);
+        assert_eq!(m.height(), 4);
+        assert_eq!(m.to_coordinate(2), Some(Coordinate::new(0, 3)));
+        assert_eq!(m.to_linear(Coordinate::new(0, 1)), None);
+
+        let m = RowLengths::new(&[]);
+        assert_eq!(m.linear_len(), 0);
+        assert_eq!(m.to_coordinate(0), None);
+        assert_eq!(m.to_linear(Coordinate::new(0, 0)), None);
+    }
+
+    #[test]
+    fn index_table() {
+        assert_eq!(IndexTable::new(0, vec![Some(0)]), None);
+        assert_eq!(IndexTable::new(2, vec![Some(0), Some(0)]), None);
+        let m = IndexTable::new(2, vec![Some(3), None, Some(0), Some(1)]).unwrap();
+        check_round_trip(&m);
+        assert_eq!(m.linear_len(), 4);
+        assert_eq!(m.to_linear(Coordinate::new(0, 0)), Some(3));
+        assert_eq!(m.to_linear(Coordinate::new(1, 0)), None);
+        assert_eq!(m.to_coordinate(1), Some(Coordinate::new(1, 1)));
+        assert_eq!(m.to_coordinate(2), None);
+    }
+
+    #[test]
+    fn adapters() {
+        let mut fuses = [false; 10];
+        let mut plane = LinearAsBitArray::new(&mut fuses[..], RowLengths::new(&[3, 5, 2]));
+        plane.set(Coordinate::new(4, 1), true);
+        assert!(plane.get(Coordinate::new(4, 1)));
+        assert!(plane.try_get(Coordinate::new(3, 0)).is_err());
+        assert!(plane.try_get(Coordinate::new(1, 2)).is_ok());
+        assert!(fuses[7]);
+
+        let stride = FixedStride::new(5, 10).unwrap();
+        let mut plane = LinearAsBitArray::new(&mut fuses[..], stride);
+        let mut linear = BitArrayAsLinear::new(&mut plane, stride);
+        assert_eq!(linear.len(), 10);
+        assert!(linear.get(7));
+        linear.set(2, true);
+        assert!(fuses[2]);
+    }
+}
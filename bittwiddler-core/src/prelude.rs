@@ -2,16 +2,47 @@
 
 #[cfg(feature = "alloc")]
 pub use crate::accessor::PropertyAccessorWithStringConv;
-pub use crate::accessor::{PropertyAccessor, PropertyAccessorWithDefault};
-pub use crate::bit_access::{BitArray, Coordinate};
+pub use crate::accessor::{InconsistentCopiesError, PropertyAccessor, PropertyAccessorWithDefault};
+pub use crate::bit_access::{
+    BitArray, BitRun, BoundedBitArray, Coordinate, CoordinateOffset, OutOfBoundsError, Rect,
+    RunDirection,
+};
+#[cfg(feature = "alloc")]
+pub use crate::bitplane::{
+    BitOrder, BitPlane, ByteOrder, BytePlane, BytePlaneSettings, Layout, PlaneSet,
+};
+pub use crate::encoding::{Gated, Gray, MsbFirst, OneHot, Signed, Thermometer};
 #[cfg(feature = "alloc")]
 pub use crate::human_text::{
     HumanLevelDynamicAccessor, HumanLevelThatHasState, HumanSinkForStatePieces,
-    PropertyAccessorDyn, StatePiece,
+    PropertyAccessorDyn, StatePiece, VarWidthDyn,
 };
+pub use crate::linear::{
+    BitArrayAsLinear, FixedStride, LinearAsBitArray, LinearBitArray, LinearMapping,
+};
+#[cfg(feature = "alloc")]
+pub use crate::linear::{IndexTable, RowLengths};
 #[cfg(feature = "alloc")]
-pub use crate::property::PropertyLeafWithStringConv;
-pub use crate::property::{PropertyLeaf, PropertyLeafWithDefault};
+pub use crate::property::{
+    _composite_split, _composite_to_string, PropertyLeafWithStringConv, StringConvError,
+};
+pub use crate::property::{PropertyLeaf, PropertyLeafWithDefault, UnrecognizedBitsError};
+pub use crate::schema::{
+    _accessor_leaf_schema, _accessor_nbits, _leaf_schema, _opaque_leaf_schema, ArgSchema,
+    FieldSchema, HumanLevelWithSchema, LeafKind, LeafSchema, LevelSchema, SubLeafSchema,
+    SublevelSchema, VariantSchema,
+};
+#[cfg(feature = "alloc")]
+pub use crate::schema::{_arg_values_item, ArgValuesIter};
+#[cfg(feature = "alloc")]
+pub use crate::tracking::{AccessCounts, AccessTracker};
+#[cfg(feature = "alloc")]
+pub use crate::var_width::{
+    VarWidthPropertyAccessor, VarWidthPropertyAccessorWithDefault,
+    VarWidthPropertyAccessorWithStringConv, VarWidthPropertyLeaf, VarWidthPropertyLeafWithDefault,
+    VarWidthPropertyLeafWithStringConv,
+};
+pub use crate::view::{MirrorView, Rotate90View, TransposeView, WindowView};
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
@@ -4,17 +4,19 @@
 //! This includes things such as integers and enums, but it can also be something
 //! entirely custom.
 
-#[cfg(feature = "alloc")]
-use core::mem::{self, MaybeUninit};
-
 #[cfg(feature = "alloc")]
 extern crate alloc;
 #[cfg(feature = "alloc")]
 use alloc::borrow::Cow;
 #[cfg(feature = "alloc")]
 use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use core::fmt::Display;
 
 use crate::accessor::PropertyAccessor;
+use crate::schema::{LeafKind, LeafSchema, VariantSchema};
 use crate::workarounds::MustBeABoolArrayConstGenericsWorkaround;
 
 /// Trait for converting between property and array of bool
@@ -24,7 +26,108 @@ pub trait PropertyLeaf<T: MustBeABoolArrayConstGenericsWorkaround> {
     fn from_bits(bits: &T) -> Self;
     /// Convert a property value to a bool array
     fn to_bits(&self) -> T;
+
+    /// Convert from a bool array to a property value, or fail if the bits aren't recognized
+    ///
+    /// Leaves where not every bool array is a valid value (and whose [PropertyLeaf::from_bits]
+    /// therefore panics) must override this. Use this instead of `from_bits`
+    /// when decoding untrusted bitstreams. The default assumes every bit pattern is valid.
+    fn checked_from_bits(bits: &T) -> Result<Self, UnrecognizedBitsError<T>>
+    where
+        Self: Sized,
+    {
+        Ok(Self::from_bits(bits))
+    }
+
+    /// Description of the values of this property, for introspection
+    const SCHEMA: LeafSchema = LeafSchema::OPAQUE;
+}
+
+/// Error for a bit pattern which doesn't correspond to any known value of a property
+///
+/// The raw bits are kept so that undocumented patterns can be reported.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct UnrecognizedBitsError<T> {
+    pub bits: T,
+}
+impl<T: AsRef<[bool]>> Display for UnrecognizedBitsError<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "unrecognized bit pattern ")?;
+        for &b in self.bits.as_ref() {
+            write!(f, "{}", if b { '1' } else { '0' })?;
+        }
+        Ok(())
+    }
+}
+impl<T: AsRef<[bool]> + core::fmt::Debug> core::error::Error for UnrecognizedBitsError<T> {}
+
+/// Error for text which couldn't be converted to a property value or an argument
+///
+/// This records the offending text, descriptions of the forms which would have been accepted
+/// (e.g. the names of every variant of an enum), and which argument failed (if any).
+#[cfg(feature = "alloc")]
+#[derive(Clone, PartialEq, Eq, Debug, Default, Hash)]
+pub struct StringConvError {
+    pub text: String,
+    pub expected: Vec<Cow<'static, str>>,
+    pub arg: Option<Cow<'static, str>>,
 }
+#[cfg(feature = "alloc")]
+impl StringConvError {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.into(),
+            expected: Vec::new(),
+            arg: None,
+        }
+    }
+
+    /// Error for the wrong number of arguments
+    pub fn wrong_arg_count(args: &[&str], expected: usize) -> Self {
+        let plural = if expected == 1 { "" } else { "s" };
+        Self::new(&args.join(", ")).expected(alloc::format!("{} argument{}", expected, plural))
+    }
+
+    /// Add one form of text which would have been accepted
+    pub fn expected(mut self, form: impl Into<Cow<'static, str>>) -> Self {
+        self.expected.push(form.into());
+        self
+    }
+
+    /// Add several forms of text which would have been accepted
+    pub fn expected_one_of(mut self, forms: &[&'static str]) -> Self {
+        self.expected
+            .extend(forms.iter().map(|&x| Cow::Borrowed(x)));
+        self
+    }
+
+    /// Record which argument this error came from
+    ///
+    /// If this error already came from an argument, the innermost one is kept.
+    pub fn for_arg(mut self, arg: impl Into<Cow<'static, str>>) -> Self {
+        if self.arg.is_none() {
+            self.arg = Some(arg.into());
+        }
+        self
+    }
+}
+#[cfg(feature = "alloc")]
+impl Display for StringConvError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid value \"{}\"", self.text)?;
+        if let Some(arg) = &self.arg {
+            write!(f, " for argument {}", arg)?;
+        }
+        match self.expected.len() {
+            0 => {}
+            1 => write!(f, ", expected {}", self.expected[0])?,
+            _ => write!(f, ", expected one of: {}", self.expected.join(", "))?,
+        }
+        Ok(())
+    }
+}
+#[cfg(feature = "alloc")]
+impl core::error::Error for StringConvError {}
 
 /// Trait for checking whether or not this field is at its default value
 ///
@@ -75,31 +178,82 @@ pub trait PropertyLeafWithStringConv<
         }
         s.into()
     }
-    fn from_string(s: &str, _accessor: &A) -> Result<Self, ()>
+    fn from_string(s: &str, _accessor: &A) -> Result<Self, StringConvError>
     where
         Self: Sized,
     {
+        let err = || StringConvError::new(s).expected(alloc::format!("{} bits", T::NBITS));
         if s.len() != T::NBITS {
-            return Err(());
+            return Err(err());
         }
 
-        // safety: T::MaybeUninitTy is an array of MaybeUninit which doesn't require init
-        let mut bits: T::MaybeUninitTy = unsafe { MaybeUninit::uninit().assume_init() };
+        let mut bits = T::from_fn(|_| false);
         for (i, c) in s.chars().enumerate() {
             if c == '1' {
-                bits.as_mut()[i].write(true);
+                bits.as_mut()[i] = true;
             } else if c == '0' {
-                bits.as_mut()[i].write(false);
+                bits.as_mut()[i] = false;
             } else {
-                return Err(());
+                return Err(err());
             }
         }
-        // safety: converting between the same memory representation, guaranteed by MaybeUninit
-        let bits = unsafe { mem::transmute_copy::<_, T>(&bits) };
         Ok(Self::from_bits(&bits))
     }
 }
 
+/// Writes the string form of a composite property, `{a=1, b=2}`
+///
+/// This is used by `#[derive(PropertyLeaf)]`.
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub fn _composite_to_string(fields: &[(&str, Cow<'static, str>)]) -> Cow<'static, str> {
+    let mut s = String::from("{");
+    for (i, (name, val)) in fields.iter().enumerate() {
+        if i != 0 {
+            s.push_str(", ");
+        }
+        s.push_str(name);
+        s.push('=');
+        s.push_str(val);
+    }
+    s.push('}');
+    s.into()
+}
+
+/// Splits the string form of a composite property, `{a=1, b=2}`, into names and values
+///
+/// Commas inside of nested brackets don't split fields, so composites can be nested.
+/// This is used by `#[derive(PropertyLeaf)]`.
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub fn _composite_split(s: &str) -> Option<Vec<(&str, &str)>> {
+    let s = s.trim();
+    let s = s.strip_prefix('{')?.strip_suffix('}')?;
+    let mut ret = Vec::new();
+    if s.trim().is_empty() {
+        return Some(ret);
+    }
+
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in s.char_indices().chain(core::iter::once((s.len(), ','))) {
+        match c {
+            '{' | '(' | '[' => depth += 1,
+            '}' | ')' | ']' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                let (name, val) = s[start..i].split_once('=')?;
+                ret.push((name.trim(), val.trim()));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return None;
+    }
+    Some(ret)
+}
+
 // impl PropertyLeaf for bool and integers below
 
 impl PropertyLeaf<[bool; 1]> for bool {
@@ -110,6 +264,24 @@ impl PropertyLeaf<[bool; 1]> for bool {
     fn to_bits(&self) -> [bool; 1] {
         [*self]
     }
+
+    const SCHEMA: LeafSchema = LeafSchema {
+        doc: None,
+        kind: LeafKind::Enum(&[
+            VariantSchema {
+                name: "false",
+                doc: None,
+                pattern: Some("0"),
+                keeps_bits: false,
+            },
+            VariantSchema {
+                name: "true",
+                doc: None,
+                pattern: Some("1"),
+                keeps_bits: false,
+            },
+        ]),
+    };
 }
 #[cfg(feature = "alloc")]
 impl<A: PropertyAccessor> PropertyLeafWithStringConv<[bool; 1], A> for bool {
@@ -121,14 +293,14 @@ impl<A: PropertyAccessor> PropertyLeafWithStringConv<[bool; 1], A> for bool {
         }
     }
 
-    fn from_string(s: &str, _accessor: &A) -> Result<Self, ()>
+    fn from_string(s: &str, _accessor: &A) -> Result<Self, StringConvError>
     where
         Self: Sized,
     {
         match s {
             "true" | "1" => Ok(true),
             "false" | "0" => Ok(false),
-            _ => Err(()),
+            _ => Err(StringConvError::new(s).expected_one_of(&["true", "false"])),
         }
     }
 }
@@ -161,15 +333,22 @@ macro_rules! impl_bit_prop_for_int {
             fn to_string(&self, _accessor: &A) -> Cow<'static, str> {
                 alloc::format!("0x{self:X}").into()
             }
-            fn from_string(s: &str, _accessor: &A) -> Result<Self, ()> {
-                if let Some(s) = s.strip_prefix("0x") {
-                    Self::from_str_radix(s, 16)
-                } else if let Some(s) = s.strip_prefix("0b") {
-                    Self::from_str_radix(s, 2)
+            fn from_string(s: &str, _accessor: &A) -> Result<Self, StringConvError> {
+                let err =
+                    || StringConvError::new(s).expected(alloc::format!("{}-bit integer", $nbits));
+                let val = if let Some(x) = s.strip_prefix("0x") {
+                    Self::from_str_radix(x, 16)
+                } else if let Some(x) = s.strip_prefix("0b") {
+                    Self::from_str_radix(x, 2)
                 } else {
                     Self::from_str_radix(s, 10)
                 }
-                .map_err(|_| ())
+                .map_err(|_| err())?;
+                // reject values which don't fit in the property
+                if val.checked_shr($nbits).unwrap_or(0) != 0 {
+                    return Err(err());
+                }
+                Ok(val)
             }
         }
     };
@@ -427,3 +606,84 @@ impl_bit_prop_for_int!(125, u128);
 impl_bit_prop_for_int!(126, u128);
 impl_bit_prop_for_int!(127, u128);
 impl_bit_prop_for_int!(128, u128);
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn composite_strings() {
+        assert_eq!(
+            _composite_to_string(&[("div", "0x3".into()), ("en", "true".into())]),
+            "{div=0x3, en=true}"
+        );
+        assert_eq!(
+            _composite_split("{div=0x3, inner={a=1, b=(2, 3)}, en = true}"),
+            Some(alloc::vec![
+                ("div", "0x3"),
+                ("inner", "{a=1, b=(2, 3)}"),
+                ("en", "true")
+            ])
+        );
+        assert_eq!(_composite_split("{}"), Some(alloc::vec![]));
+        assert_eq!(_composite_split("div=3"), None);
+        assert_eq!(_composite_split("{div}"), None);
+        assert_eq!(_composite_split("{a={b=1}"), None);
+    }
+
+    #[test]
+    fn int_string_conv() {
+        use crate::bit_access::Coordinate;
+
+        struct Dummy;
+        impl PropertyAccessor for Dummy {
+            type BoolArray = [bool; 4];
+            type Output = u8;
+
+            fn get_bit_pos(&self, biti: usize) -> (Coordinate, bool) {
+                (Coordinate::new(biti, 0), false)
+            }
+        }
+
+        let conv = |s| <u8 as PropertyLeafWithStringConv<[bool; 4], _>>::from_string(s, &Dummy);
+        assert_eq!(conv("0xF"), Ok(15));
+        assert_eq!(conv("0b1010"), Ok(10));
+        // too wide values aren't truncated
+        assert_eq!(
+            alloc::string::ToString::to_string(&conv("0x10").unwrap_err()),
+            "invalid value \"0x10\", expected 4-bit integer"
+        );
+        assert!(conv("255").is_err());
+        assert_eq!(
+            <u128 as PropertyLeafWithStringConv<[bool; 128], Dummy>>::from_string(
+                "0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+                &Dummy
+            ),
+            Ok(u128::MAX)
+        );
+    }
+
+    #[test]
+    fn string_conv_error() {
+        let e = StringConvError::new("maybe");
+        assert_eq!(
+            alloc::string::ToString::to_string(&e),
+            "invalid value \"maybe\""
+        );
+        let e = e.expected_one_of(&["true", "false"]).for_arg("en");
+        assert_eq!(
+            alloc::string::ToString::to_string(&e),
+            "invalid value \"maybe\" for argument en, expected one of: true, false"
+        );
+        // the innermost argument is kept
+        assert_eq!(e.for_arg("outer").arg.as_deref(), Some("en"));
+        assert_eq!(
+            alloc::string::ToString::to_string(&StringConvError::wrong_arg_count(&["1", "2"], 1)),
+            "invalid value \"1, 2\", expected 1 argument"
+        );
+        assert_eq!(
+            alloc::string::ToString::to_string(&StringConvError::wrong_arg_count(&["1"], 2)),
+            "invalid value \"1\", expected 2 arguments"
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! Contains static descriptions of hierarchy levels and properties
+//!
+//! These are generated by the macros so that tools (editors, GUIs, documentation generators, etc.)
+//! can discover everything which can be accessed starting from the root bitstream,
+//! without needing to construct any accessors.
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+use crate::accessor::PropertyAccessor;
+#[cfg(feature = "alloc")]
+use crate::human_text::StatePiece;
+use crate::property::PropertyLeaf;
+use crate::workarounds::MustBeABoolArrayConstGenericsWorkaround;
+
+/// Description of one hierarchy level
+#[derive(Clone, Copy, Debug)]
+pub struct LevelSchema {
+    /// Name of the type implementing this level
+    pub name: &'static str,
+    pub sublevels: &'static [SublevelSchema],
+    pub fields: &'static [FieldSchema],
+}
+impl LevelSchema {
+    pub fn sublevel(&self, name: &str) -> Option<&'static SublevelSchema> {
+        self.sublevels.iter().find(|x| x.name == name)
+    }
+    pub fn field(&self, name: &str) -> Option<&'static FieldSchema> {
+        self.fields.iter().find(|x| x.name == name)
+    }
+}
+
+/// Description of one argument needed to construct a sublevel or property
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ArgSchema {
+    pub name: &'static str,
+    /// Source text of the argument's type, e.g. `Vec<u8>`
+    pub ty: &'static str,
+    /// Source text of the `#[bittwiddler::range]` or `#[bittwiddler::values]` expression, if any
+    ///
+    /// This is only meant to be shown to people. Use `arg_values` on the sublevel or field
+    /// to actually enumerate the values.
+    pub values: Option<&'static str>,
+}
+
+/// Every combination of argument values, in the same form as in human-readable files
+///
+/// Each item has one string for every argument, in the order they are declared.
+#[cfg(feature = "alloc")]
+pub type ArgValuesIter = Box<dyn Iterator<Item = Vec<String>>>;
+
+/// Description of a sublevel of a hierarchy level
+#[derive(Clone, Copy, Debug)]
+pub struct SublevelSchema {
+    pub name: &'static str,
+    pub doc: Option<&'static str>,
+    pub args: &'static [ArgSchema],
+    /// Whether this only exists for some values of the arguments
+    pub conditional: bool,
+    /// Enumerates the arguments of every instance of this sublevel
+    ///
+    /// This is `None` if the instances can't be known without constructing the parent level,
+    /// i.e. if it is conditional, if the arguments don't all have a range,
+    /// or if a range refers to `self`.
+    #[cfg(feature = "alloc")]
+    pub arg_values: Option<fn() -> ArgValuesIter>,
+    /// The sublevel itself (this is a function so that levels can refer to each other)
+    pub level: fn() -> &'static LevelSchema,
+}
+
+/// Description of a property (field) of a hierarchy level
+#[derive(Clone, Copy, Debug)]
+pub struct FieldSchema {
+    pub name: &'static str,
+    pub doc: Option<&'static str>,
+    pub args: &'static [ArgSchema],
+    /// Whether this only exists for some values of the arguments
+    pub conditional: bool,
+    /// Enumerates the arguments of every instance of this property, like [SublevelSchema::arg_values]
+    #[cfg(feature = "alloc")]
+    pub arg_values: Option<fn() -> ArgValuesIter>,
+    /// Source text of the accessor's type
+    pub accessor: &'static str,
+    /// Number of bits, or `None` for runtime-width properties
+    pub nbits: Option<usize>,
+    /// The values this property can hold
+    pub leaf: fn() -> LeafSchema,
+}
+
+/// Description of the values of a property leaf type
+///
+/// This is [PropertyLeaf::SCHEMA], which generated enums and composites fill in.
+#[derive(Clone, Copy, Debug)]
+pub struct LeafSchema {
+    pub doc: Option<&'static str>,
+    pub kind: LeafKind,
+}
+impl LeafSchema {
+    /// Nothing is known about the values
+    pub const OPAQUE: Self = Self {
+        doc: None,
+        kind: LeafKind::Opaque,
+    };
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum LeafKind {
+    Opaque,
+    /// Every valid value has a name
+    Enum(&'static [VariantSchema]),
+    /// The value is made up of sub-leaves, from `#[derive(PropertyLeaf)]`
+    Composite(&'static [SubLeafSchema]),
+}
+
+/// Description of one variant of an enum property leaf
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VariantSchema {
+    /// Name used in human-readable files
+    pub name: &'static str,
+    pub doc: Option<&'static str>,
+    /// Bit pattern in the same syntax as bit property files (`0`, `1`, `x`, `X`),
+    /// or `None` for a catchall
+    pub pattern: Option<&'static str>,
+    /// Whether the raw bits are kept, in which case the string form is `name(bits)`
+    pub keeps_bits: bool,
+}
+
+/// Description of one piece of a composite property leaf
+#[derive(Clone, Copy, Debug)]
+pub struct SubLeafSchema {
+    pub name: &'static str,
+    pub offset: usize,
+    pub nbits: usize,
+    pub leaf: fn() -> LeafSchema,
+}
+
+/// Used by the macros to describe a level
+pub trait HumanLevelWithSchema {
+    fn _human_schema() -> &'static LevelSchema;
+}
+
+#[doc(hidden)]
+pub const fn _accessor_nbits<A: PropertyAccessor>() -> usize {
+    A::BoolArray::NBITS
+}
+#[doc(hidden)]
+pub fn _accessor_leaf_schema<A: PropertyAccessor>() -> LeafSchema {
+    <A::Output as PropertyLeaf<A::BoolArray>>::SCHEMA
+}
+#[doc(hidden)]
+#[allow(private_bounds)]
+pub fn _leaf_schema<T: MustBeABoolArrayConstGenericsWorkaround, L: PropertyLeaf<T>>() -> LeafSchema
+{
+    L::SCHEMA
+}
+#[doc(hidden)]
+pub fn _opaque_leaf_schema() -> LeafSchema {
+    LeafSchema::OPAQUE
+}
+#[doc(hidden)]
+#[cfg(feature = "alloc")]
+pub fn _arg_values_item<const N: usize>(args: [&dyn StatePiece; N]) -> Vec<String> {
+    args.iter()
+        .map(|x| x.to_human_string().into_owned())
+        .collect()
+}
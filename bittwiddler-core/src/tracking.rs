@@ -0,0 +1,177 @@
+//! Contains a wrapper which records which bits of a [BitArray] were accessed
+//!
+//! This is intended for reverse engineering, e.g. to find which set bits
+//! in a bitstream aren't covered by any known property.
+
+use core::cell::RefCell;
+
+extern crate alloc;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::bit_access::{BitArray, BitRun, BoundedBitArray, Coordinate, RunDirection};
+
+/// How many times one bit was accessed
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Hash)]
+pub struct AccessCounts {
+    pub reads: usize,
+    pub writes: usize,
+}
+
+/// Wraps a [BitArray] and records every coordinate that is read or written
+pub struct AccessTracker<B> {
+    inner: B,
+    accesses: RefCell<BTreeMap<Coordinate, AccessCounts>>,
+}
+impl<B: BitArray> AccessTracker<B> {
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            accesses: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Forget all accesses recorded so far
+    pub fn clear(&mut self) {
+        self.accesses.get_mut().clear();
+    }
+
+    /// How many times the bit at `c` has been accessed
+    pub fn counts(&self, c: Coordinate) -> AccessCounts {
+        self.accesses.borrow().get(&c).copied().unwrap_or_default()
+    }
+
+    /// Every coordinate which has been accessed at least once, in sorted order
+    pub fn touched(&self) -> Vec<(Coordinate, AccessCounts)> {
+        self.accesses
+            .borrow()
+            .iter()
+            .map(|(c, counts)| (*c, *counts))
+            .collect()
+    }
+
+    fn record_read(&self, c: Coordinate) {
+        self.accesses.borrow_mut().entry(c).or_default().reads += 1;
+    }
+    fn record_write(&self, c: Coordinate) {
+        self.accesses.borrow_mut().entry(c).or_default().writes += 1;
+    }
+}
+impl<B: BoundedBitArray> AccessTracker<B> {
+    /// Every set bit which has never been accessed, in row-major order (plane by plane)
+    pub fn untouched_set_bits(&self) -> Vec<Coordinate> {
+        let accesses = self.accesses.borrow();
+        self.inner
+            .all_coordinates()
+            .filter(|c| self.inner.get(*c) && !accesses.contains_key(c))
+            .collect()
+    }
+}
+impl<B: BitArray> BitArray for AccessTracker<B> {
+    fn get(&self, c: Coordinate) -> bool {
+        self.record_read(c);
+        self.inner.get(c)
+    }
+    fn set(&mut self, c: Coordinate, val: bool) {
+        self.record_write(c);
+        self.inner.set(c, val)
+    }
+
+    fn get_run(&self, start: Coordinate, dir: RunDirection, len: usize) -> Option<u64> {
+        // if this returns None, the caller will fall back to get() which will record the bits
+        let bits = self.inner.get_run(start, dir, len)?;
+        let run = BitRun {
+            start,
+            dir,
+            len,
+            invert: false,
+        };
+        for i in 0..len {
+            self.record_read(run.coordinate(i));
+        }
+        Some(bits)
+    }
+    fn set_run(&mut self, start: Coordinate, dir: RunDirection, len: usize, bits: u64) -> bool {
+        if !self.inner.set_run(start, dir, len, bits) {
+            return false;
+        }
+        let run = BitRun {
+            start,
+            dir,
+            len,
+            invert: false,
+        };
+        for i in 0..len {
+            self.record_write(run.coordinate(i));
+        }
+        true
+    }
+}
+impl<B: BoundedBitArray> BoundedBitArray for AccessTracker<B> {
+    fn width(&self) -> usize {
+        self.inner.width()
+    }
+    fn height(&self) -> usize {
+        self.inner.height()
+    }
+    fn num_planes(&self) -> usize {
+        self.inner.num_planes()
+    }
+    fn plane_width(&self, plane: usize) -> usize {
+        self.inner.plane_width(plane)
+    }
+    fn plane_height(&self, plane: usize) -> usize {
+        self.inner.plane_height(plane)
+    }
+    fn in_bounds(&self, c: Coordinate) -> bool {
+        self.inner.in_bounds(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitplane::BitPlane;
+
+    #[test]
+    fn tracking() {
+        let mut plane = BitPlane::new(8, 4);
+        plane.set(Coordinate::new(1, 1), true);
+        plane.set(Coordinate::new(2, 1), true);
+        plane.set(Coordinate::new(7, 3), true);
+
+        let mut t = AccessTracker::new(plane);
+        assert!(t.get(Coordinate::new(1, 1)));
+        assert!(!t.get(Coordinate::new(0, 0)));
+        assert!(!t.get(Coordinate::new(0, 0)));
+        t.set(Coordinate::new(0, 0), true);
+        assert_eq!(
+            t.get_run(Coordinate::new(0, 3), RunDirection::Right, 4),
+            Some(0)
+        );
+
+        assert_eq!(
+            t.counts(Coordinate::new(0, 0)),
+            AccessCounts {
+                reads: 2,
+                writes: 1
+            }
+        );
+        assert_eq!(t.counts(Coordinate::new(3, 3)).reads, 1);
+        assert_eq!(t.touched().len(), 6);
+        assert_eq!(
+            t.untouched_set_bits(),
+            [Coordinate::new(2, 1), Coordinate::new(7, 3)]
+        );
+
+        t.clear();
+        assert_eq!(t.touched().len(), 0);
+    }
+}
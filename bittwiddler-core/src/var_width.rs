@@ -0,0 +1,317 @@
+//! Contains traits for properties whose width is only known at runtime
+//!
+//! [PropertyAccessor](crate::accessor::PropertyAccessor) requires the number of bits
+//! to be a compile-time constant. Some fields instead depend on e.g. which part or which
+//! column they are in (such as the number of inputs to a routing mux).
+//! These traits work on bool slices instead of bool arrays.
+
+extern crate alloc;
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::bit_access::{BitArray, BoundedBitArray, Coordinate, OutOfBoundsError};
+use crate::property::StringConvError;
+
+/// Trait for converting between property and a slice of bool, where the slice length can vary
+pub trait VarWidthPropertyLeaf {
+    /// Convert from bits to a property value
+    fn from_bits(bits: &[bool]) -> Self;
+    /// Convert a property value to bits, filling all of `bits`
+    fn to_bits(&self, bits: &mut [bool]);
+}
+
+/// Trait for checking whether or not this field is at its default value
+///
+/// This is used for creating human-readable files.
+pub trait VarWidthPropertyLeafWithDefault<A: VarWidthPropertyAccessor + ?Sized>:
+    VarWidthPropertyLeaf
+{
+    fn is_default(&self, accessor: &A) -> bool;
+}
+
+/// Trait for converting between property and a string
+///
+/// This is used for creating human-readable files.
+///
+/// The default implementation converts to a string of '0' and '1' characters
+/// corresponding to the raw bits.
+pub trait VarWidthPropertyLeafWithStringConv<A: VarWidthPropertyAccessor + ?Sized>:
+    VarWidthPropertyLeaf
+{
+    fn to_string(&self, accessor: &A) -> Cow<'static, str> {
+        let mut bits = vec![false; accessor.nbits()];
+        self.to_bits(&mut bits);
+        bits.iter()
+            .map(|b| if *b { '1' } else { '0' })
+            .collect::<String>()
+            .into()
+    }
+    fn from_string(s: &str, accessor: &A) -> Result<Self, StringConvError>
+    where
+        Self: Sized,
+    {
+        let err = || StringConvError::new(s).expected(alloc::format!("{} bits", accessor.nbits()));
+        if s.len() != accessor.nbits() {
+            return Err(err());
+        }
+        let bits = s
+            .chars()
+            .map(|c| match c {
+                '1' => Ok(true),
+                '0' => Ok(false),
+                _ => Err(err()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::from_bits(&bits))
+    }
+}
+
+/// Like [PropertyAccessor](crate::accessor::PropertyAccessor), but the number of bits
+/// is returned by [VarWidthPropertyAccessor::nbits] instead of being part of the type
+pub trait VarWidthPropertyAccessor {
+    /// Must be the friendly type of this property
+    type Output: VarWidthPropertyLeaf;
+
+    /// Must implement this. Return how many bits this property has.
+    fn nbits(&self) -> usize;
+    /// Must implement this. Return a coordinate and invert Y/N for each bit of this property.
+    fn get_bit_pos(&self, biti: usize) -> (Coordinate, bool);
+
+    /// Automatically read all the bits and convert them to a friendly type
+    fn get(&self, bitstream: &(impl BitArray + ?Sized)) -> Self::Output {
+        let bits = (0..self.nbits())
+            .map(|biti| {
+                let (c, inv) = self.get_bit_pos(biti);
+                bitstream.get(c) ^ inv
+            })
+            .collect::<Vec<_>>();
+        Self::Output::from_bits(&bits)
+    }
+    /// Automatically convert from a friendly type value to bits and write them to the correct coordinates
+    fn set(&self, bitstream: &mut (impl BitArray + ?Sized), val: Self::Output) {
+        let mut bits = vec![false; self.nbits()];
+        val.to_bits(&mut bits);
+        for (biti, bit) in bits.into_iter().enumerate() {
+            let (c, inv) = self.get_bit_pos(biti);
+            bitstream.set(c, bit ^ inv);
+        }
+    }
+
+    /// Like [VarWidthPropertyAccessor::get], but returns an error if any bit is outside of the bitstream
+    fn try_get(
+        &self,
+        bitstream: &(impl BoundedBitArray + ?Sized),
+    ) -> Result<Self::Output, OutOfBoundsError> {
+        self.check_bounds(bitstream)?;
+        Ok(self.get(bitstream))
+    }
+    /// Like [VarWidthPropertyAccessor::set], but returns an error if any bit is outside of the bitstream
+    ///
+    /// Nothing is written if any bit is out of bounds.
+    fn try_set(
+        &self,
+        bitstream: &mut (impl BoundedBitArray + ?Sized),
+        val: Self::Output,
+    ) -> Result<(), OutOfBoundsError> {
+        self.check_bounds(bitstream)?;
+        self.set(bitstream, val);
+        Ok(())
+    }
+    /// Check that every bit of this property is inside the bitstream
+    fn check_bounds(
+        &self,
+        bitstream: &(impl BoundedBitArray + ?Sized),
+    ) -> Result<(), OutOfBoundsError> {
+        for biti in 0..self.nbits() {
+            let (c, _) = self.get_bit_pos(biti);
+            if !bitstream.in_bounds(c) {
+                return Err(OutOfBoundsError { coordinate: c });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Allows querying if the setting at a location is default or not
+///
+/// This is used for creating human-readable files.
+pub trait VarWidthPropertyAccessorWithDefault: VarWidthPropertyAccessor
+where
+    Self::Output: VarWidthPropertyLeafWithDefault<Self>,
+{
+    fn is_at_default(&self, bitstream: &(impl BitArray + ?Sized)) -> bool {
+        let val = self.get(bitstream);
+        val.is_default(self)
+    }
+}
+
+/// Allows interacting with this property using strings instead of typed objects
+///
+/// This is used for creating human-readable files.
+pub trait VarWidthPropertyAccessorWithStringConv: VarWidthPropertyAccessor
+where
+    Self::Output: VarWidthPropertyLeafWithStringConv<Self>,
+{
+    fn get_as_string(&self, bitstream: &(impl BitArray + ?Sized)) -> Cow<'static, str> {
+        let val = self.get(bitstream);
+        val.to_string(self)
+    }
+    fn set_from_string(
+        &self,
+        bitstream: &mut (impl BitArray + ?Sized),
+        val: &str,
+    ) -> Result<(), StringConvError> {
+        let val = Self::Output::from_string(val, self)?;
+        self.set(bitstream, val);
+        Ok(())
+    }
+}
+
+// impl VarWidthPropertyLeaf for raw bits and integers below
+
+/// Panics if the number of bits is different from the accessor's
+impl VarWidthPropertyLeaf for Vec<bool> {
+    fn from_bits(bits: &[bool]) -> Self {
+        bits.to_vec()
+    }
+    fn to_bits(&self, bits: &mut [bool]) {
+        assert_eq!(
+            self.len(),
+            bits.len(),
+            "Vec<bool> property value has the wrong number of bits"
+        );
+        bits.copy_from_slice(self);
+    }
+}
+impl<A: VarWidthPropertyAccessor + ?Sized> VarWidthPropertyLeafWithDefault<A> for Vec<bool> {
+    fn is_default(&self, _accessor: &A) -> bool {
+        self.iter().all(|b| !*b)
+    }
+}
+impl<A: VarWidthPropertyAccessor + ?Sized> VarWidthPropertyLeafWithStringConv<A> for Vec<bool> {}
+
+macro_rules! impl_var_width_prop_for_int {
+    ($int_ty:ty) => {
+        /// Panics if there are more bits than fit in this type
+        impl VarWidthPropertyLeaf for $int_ty {
+            fn from_bits(bits: &[bool]) -> Self {
+                assert!(bits.len() <= <$int_ty>::BITS as usize);
+                let mut ret = 0;
+                for (i, bit) in bits.iter().enumerate() {
+                    if *bit {
+                        ret |= 1 << i;
+                    }
+                }
+                ret
+            }
+            fn to_bits(&self, bits: &mut [bool]) {
+                assert!(bits.len() <= <$int_ty>::BITS as usize);
+                for (i, bit) in bits.iter_mut().enumerate() {
+                    *bit = (self & (1 << i)) != 0;
+                }
+            }
+        }
+        impl<A: VarWidthPropertyAccessor + ?Sized> VarWidthPropertyLeafWithDefault<A> for $int_ty {
+            fn is_default(&self, _accessor: &A) -> bool {
+                *self == 0
+            }
+        }
+        impl<A: VarWidthPropertyAccessor + ?Sized> VarWidthPropertyLeafWithStringConv<A>
+            for $int_ty
+        {
+            fn to_string(&self, _accessor: &A) -> Cow<'static, str> {
+                alloc::format!("0x{self:X}").into()
+            }
+            fn from_string(s: &str, accessor: &A) -> Result<Self, StringConvError> {
+                let err = || {
+                    StringConvError::new(s)
+                        .expected(alloc::format!("{}-bit integer", accessor.nbits()))
+                };
+                let val = if let Some(x) = s.strip_prefix("0x") {
+                    Self::from_str_radix(x, 16)
+                } else if let Some(x) = s.strip_prefix("0b") {
+                    Self::from_str_radix(x, 2)
+                } else {
+                    Self::from_str_radix(s, 10)
+                }
+                .map_err(|_| err())?;
+                // reject values which don't fit in the property
+                if accessor.nbits() < <$int_ty>::BITS as usize && val >> accessor.nbits() != 0 {
+                    return Err(err());
+                }
+                Ok(val)
+            }
+        }
+    };
+}
+
+impl_var_width_prop_for_int!(u8);
+impl_var_width_prop_for_int!(u16);
+impl_var_width_prop_for_int!(u32);
+impl_var_width_prop_for_int!(u64);
+impl_var_width_prop_for_int!(u128);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitplane::BitPlane;
+
+    struct Column {
+        x: usize,
+        height: usize,
+    }
+    impl VarWidthPropertyAccessor for Column {
+        type Output = u16;
+
+        fn nbits(&self) -> usize {
+            self.height
+        }
+        fn get_bit_pos(&self, biti: usize) -> (Coordinate, bool) {
+            (Coordinate::new(self.x, biti), biti == 0)
+        }
+    }
+    impl VarWidthPropertyAccessorWithDefault for Column {}
+    impl VarWidthPropertyAccessorWithStringConv for Column {}
+
+    #[test]
+    fn var_width_get_set() {
+        let mut p = BitPlane::new(2, 8);
+        let short = Column { x: 0, height: 3 };
+        let long = Column { x: 1, height: 8 };
+
+        assert_eq!(short.get(&p), 1);
+        short.set(&mut p, 0b011);
+        assert!(!p.get(Coordinate::new(0, 0)));
+        assert!(p.get(Coordinate::new(0, 1)));
+        assert!(!p.get(Coordinate::new(0, 2)));
+        assert_eq!(short.get(&p), 0b011);
+
+        long.set(&mut p, 0xA5);
+        assert_eq!(long.get_as_string(&p), "0xA5");
+        assert!(!long.is_at_default(&p));
+        assert_eq!(
+            short.set_from_string(&mut p, "0x8"),
+            Err(StringConvError::new("0x8").expected("3-bit integer"))
+        );
+        assert_eq!(long.set_from_string(&mut p, "0x0"), Ok(()));
+        assert!(long.is_at_default(&p));
+
+        assert!(Column { x: 1, height: 9 }.try_get(&p).is_err());
+    }
+
+    #[test]
+    fn vec_bool_to_bits() {
+        let mut bits = [false; 3];
+        vec![true, false, true].to_bits(&mut bits);
+        assert_eq!(bits, [true, false, true]);
+    }
+
+    #[test]
+    #[should_panic(expected = "wrong number of bits")]
+    fn vec_bool_wrong_length() {
+        let mut bits = [false; 3];
+        vec![true; 4].to_bits(&mut bits);
+    }
+}
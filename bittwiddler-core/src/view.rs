@@ -0,0 +1,344 @@
+//! Contains "views" which change the coordinates used to access an underlying [BitArray]
+//!
+//! This allows e.g. an accessor to be written once using tile-local coordinates,
+//! and then reused for tile instances which are flipped or rotated.
+//! Views can wrap other views in order to combine their effects.
+//!
+//! Views with an `origin` only cover bits in `origin`'s plane,
+//! and the plane of the coordinates passed to them is ignored.
+//! Accessing a coordinate outside of such a view's width and height will panic
+//! (use [BoundedBitArray::try_get] to avoid this).
+
+use crate::bit_access::{BitArray, BoundedBitArray, Coordinate, RunDirection};
+
+/// Panics if `c` is outside of a `width` x `height` view
+fn check_in_view(c: Coordinate, width: usize, height: usize) {
+    if c.x >= width || c.y >= height {
+        panic!(
+            "coordinate {} is outside of the {}x{} view",
+            Coordinate::new(c.x, c.y),
+            width,
+            height
+        );
+    }
+}
+
+/// Whether every bit of a run is inside of a `width` x `height` view
+fn run_in_view(
+    start: Coordinate,
+    dir: RunDirection,
+    len: usize,
+    width: usize,
+    height: usize,
+) -> bool {
+    match dir {
+        RunDirection::Right => start.y < height && start.x <= width && len <= width - start.x,
+        RunDirection::Down => start.x < width && start.y <= height && len <= height - start.y,
+    }
+}
+
+/// A `width` x `height` window whose upper-left corner is at `origin`
+pub struct WindowView<'a> {
+    inner: &'a mut dyn BitArray,
+    origin: Coordinate,
+    width: usize,
+    height: usize,
+}
+impl<'a> WindowView<'a> {
+    pub fn new(
+        inner: &'a mut dyn BitArray,
+        origin: Coordinate,
+        width: usize,
+        height: usize,
+    ) -> Self {
+        Self {
+            inner,
+            origin,
+            width,
+            height,
+        }
+    }
+}
+impl<'a> BitArray for WindowView<'a> {
+    fn get(&self, c: Coordinate) -> bool {
+        check_in_view(c, self.width, self.height);
+        self.inner.get(self.origin + c)
+    }
+    fn set(&mut self, c: Coordinate, val: bool) {
+        check_in_view(c, self.width, self.height);
+        self.inner.set(self.origin + c, val)
+    }
+    fn get_run(&self, start: Coordinate, dir: RunDirection, len: usize) -> Option<u64> {
+        if !run_in_view(start, dir, len, self.width, self.height) {
+            return None;
+        }
+        self.inner.get_run(self.origin + start, dir, len)
+    }
+    fn set_run(&mut self, start: Coordinate, dir: RunDirection, len: usize, bits: u64) -> bool {
+        if !run_in_view(start, dir, len, self.width, self.height) {
+            return false;
+        }
+        self.inner.set_run(self.origin + start, dir, len, bits)
+    }
+}
+impl<'a> BoundedBitArray for WindowView<'a> {
+    fn width(&self) -> usize {
+        self.width
+    }
+    fn height(&self) -> usize {
+        self.height
+    }
+}
+
+/// Mirrors the `width` x `height` rectangle whose upper-left corner is at `origin`
+///
+/// `flip_x` mirrors left-to-right and `flip_y` mirrors top-to-bottom.
+/// Coordinates are relative to `origin`.
+pub struct MirrorView<'a> {
+    inner: &'a mut dyn BitArray,
+    origin: Coordinate,
+    width: usize,
+    height: usize,
+    flip_x: bool,
+    flip_y: bool,
+}
+impl<'a> MirrorView<'a> {
+    pub fn new(
+        inner: &'a mut dyn BitArray,
+        origin: Coordinate,
+        width: usize,
+        height: usize,
+        flip_x: bool,
+        flip_y: bool,
+    ) -> Self {
+        Self {
+            inner,
+            origin,
+            width,
+            height,
+            flip_x,
+            flip_y,
+        }
+    }
+
+    fn map(&self, c: Coordinate) -> Coordinate {
+        check_in_view(c, self.width, self.height);
+        let x = if self.flip_x {
+            self.width - 1 - c.x
+        } else {
+            c.x
+        };
+        let y = if self.flip_y {
+            self.height - 1 - c.y
+        } else {
+            c.y
+        };
+        self.origin + Coordinate::new(x, y)
+    }
+}
+impl<'a> BitArray for MirrorView<'a> {
+    fn get(&self, c: Coordinate) -> bool {
+        self.inner.get(self.map(c))
+    }
+    fn set(&mut self, c: Coordinate, val: bool) {
+        self.inner.set(self.map(c), val)
+    }
+}
+impl<'a> BoundedBitArray for MirrorView<'a> {
+    fn width(&self) -> usize {
+        self.width
+    }
+    fn height(&self) -> usize {
+        self.height
+    }
+}
+
+/// Swaps x and y
+pub struct TransposeView<'a> {
+    inner: &'a mut dyn BitArray,
+}
+impl<'a> TransposeView<'a> {
+    pub fn new(inner: &'a mut dyn BitArray) -> Self {
+        Self { inner }
+    }
+
+    fn map(c: Coordinate) -> Coordinate {
+        Coordinate::new_in_plane(c.y, c.x, c.plane)
+    }
+    fn map_dir(dir: RunDirection) -> RunDirection {
+        match dir {
+            RunDirection::Right => RunDirection::Down,
+            RunDirection::Down => RunDirection::Right,
+        }
+    }
+}
+impl<'a> BitArray for TransposeView<'a> {
+    fn get(&self, c: Coordinate) -> bool {
+        self.inner.get(Self::map(c))
+    }
+    fn set(&mut self, c: Coordinate, val: bool) {
+        self.inner.set(Self::map(c), val)
+    }
+    fn get_run(&self, start: Coordinate, dir: RunDirection, len: usize) -> Option<u64> {
+        self.inner
+            .get_run(Self::map(start), Self::map_dir(dir), len)
+    }
+    fn set_run(&mut self, start: Coordinate, dir: RunDirection, len: usize, bits: u64) -> bool {
+        self.inner
+            .set_run(Self::map(start), Self::map_dir(dir), len, bits)
+    }
+}
+
+/// Rotates the `inner_width` x `inner_height` rectangle whose upper-left corner is at `origin`
+/// by 90 degrees clockwise
+///
+/// The view is therefore `inner_height` wide and `inner_width` tall.
+/// Nest multiple of these to rotate by 180 or 270 degrees.
+pub struct Rotate90View<'a> {
+    inner: &'a mut dyn BitArray,
+    origin: Coordinate,
+    inner_width: usize,
+    inner_height: usize,
+}
+impl<'a> Rotate90View<'a> {
+    pub fn new(
+        inner: &'a mut dyn BitArray,
+        origin: Coordinate,
+        inner_width: usize,
+        inner_height: usize,
+    ) -> Self {
+        Self {
+            inner,
+            origin,
+            inner_width,
+            inner_height,
+        }
+    }
+
+    fn map(&self, c: Coordinate) -> Coordinate {
+        check_in_view(c, self.inner_height, self.inner_width);
+        // the top row of the view is the left column of the inner rectangle, read upwards
+        self.origin + Coordinate::new(c.y, self.inner_height - 1 - c.x)
+    }
+}
+impl<'a> BitArray for Rotate90View<'a> {
+    fn get(&self, c: Coordinate) -> bool {
+        self.inner.get(self.map(c))
+    }
+    fn set(&mut self, c: Coordinate, val: bool) {
+        self.inner.set(self.map(c), val)
+    }
+}
+impl<'a> BoundedBitArray for Rotate90View<'a> {
+    fn width(&self) -> usize {
+        self.inner_height
+    }
+    fn height(&self) -> usize {
+        self.inner_width
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::bitplane::BitPlane;
+
+    #[test]
+    fn window() {
+        let mut p = BitPlane::new(8, 8);
+        let mut w = WindowView::new(&mut p, Coordinate::new(4, 2), 4, 4);
+        w.set(Coordinate::new(1, 1), true);
+        assert!(w.try_get(Coordinate::new(4, 0)).is_err());
+        assert!(w.set_run(Coordinate::new(0, 3), RunDirection::Right, 4, 0b1001));
+        assert!(p.get(Coordinate::new(5, 3)));
+        assert!(p.get(Coordinate::new(4, 5)));
+        assert!(p.get(Coordinate::new(7, 5)));
+        assert_eq!(p.count_ones(), 3);
+
+        // runs which leave the window aren't forwarded
+        let w = WindowView::new(&mut p, Coordinate::new(4, 2), 4, 4);
+        assert_eq!(
+            w.get_run(Coordinate::new(1, 3), RunDirection::Right, 4),
+            None
+        );
+        assert_eq!(
+            w.get_run(Coordinate::new(1, 2), RunDirection::Down, 3),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the 4x4 view")]
+    fn window_out_of_bounds() {
+        let mut p = BitPlane::new(8, 8);
+        let w = WindowView::new(&mut p, Coordinate::new(0, 0), 4, 4);
+        // would otherwise read (4, 0) of the underlying plane
+        w.get(Coordinate::new(4, 0));
+    }
+
+    #[test]
+    fn mirror() {
+        let mut p = BitPlane::new(8, 8);
+        let mut m = MirrorView::new(&mut p, Coordinate::new(4, 4), 4, 3, true, false);
+        m.set(Coordinate::new(0, 0), true);
+        assert!(p.get(Coordinate::new(7, 4)));
+
+        let mut m = MirrorView::new(&mut p, Coordinate::new(4, 4), 4, 3, true, true);
+        m.set(Coordinate::new(0, 0), true);
+        assert!(m.get(Coordinate::new(0, 2)));
+        assert!(m.try_get(Coordinate::new(4, 0)).is_err());
+        assert!(p.get(Coordinate::new(7, 6)));
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the 4x3 view")]
+    fn mirror_out_of_bounds() {
+        let mut p = BitPlane::new(8, 8);
+        let m = MirrorView::new(&mut p, Coordinate::new(4, 4), 4, 3, true, false);
+        m.get(Coordinate::new(4, 0));
+    }
+
+    #[test]
+    fn transpose() {
+        let mut p = BitPlane::new(8, 8);
+        let mut t = TransposeView::new(&mut p);
+        t.set(Coordinate::new(1, 5), true);
+        assert!(t.set_run(Coordinate::new(2, 0), RunDirection::Down, 3, 0b101));
+        assert!(!t.set_run(Coordinate::new(0, 2), RunDirection::Right, 3, 0b101));
+        assert!(p.get(Coordinate::new(5, 1)));
+        assert!(p.get(Coordinate::new(0, 2)));
+        assert!(p.get(Coordinate::new(2, 2)));
+        assert_eq!(p.count_ones(), 3);
+    }
+
+    #[test]
+    fn rotate() {
+        let mut p = BitPlane::new(8, 8);
+        // 3 wide, 2 tall rectangle at (1, 1)
+        let mut r = Rotate90View::new(&mut p, Coordinate::new(1, 1), 3, 2);
+        assert_eq!((r.width(), r.height()), (2, 3));
+        // upper-left of view is lower-left of the rectangle
+        r.set(Coordinate::new(0, 0), true);
+        assert!(p.get(Coordinate::new(1, 2)));
+        // lower-left of view is lower-right of the rectangle
+        let mut r = Rotate90View::new(&mut p, Coordinate::new(1, 1), 3, 2);
+        r.set(Coordinate::new(0, 2), true);
+        assert!(p.get(Coordinate::new(3, 2)));
+
+        // rotating twice flips both axes
+        let mut r1 = Rotate90View::new(&mut p, Coordinate::new(1, 1), 3, 2);
+        let r2 = Rotate90View::new(&mut r1, Coordinate::new(0, 0), 2, 3);
+        assert_eq!((r2.width(), r2.height()), (3, 2));
+        assert!(r2.get(Coordinate::new(0, 0)));
+        assert!(r2.get(Coordinate::new(2, 0)));
+        assert!(!r2.get(Coordinate::new(0, 1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the 2x3 view")]
+    fn rotate_out_of_bounds() {
+        let mut p = BitPlane::new(8, 8);
+        let r = Rotate90View::new(&mut p, Coordinate::new(1, 1), 3, 2);
+        r.get(Coordinate::new(2, 0));
+    }
+}
@@ -1,15 +1,18 @@
 //! Holds awful hacks
 
-use core::mem::MaybeUninit;
-
 /// This trait is used to work around limitations of min_const_generics.
 ///
 /// It is only implemented on `[bool; N]` and is deliberately hidden.
-pub trait MustBeABoolArrayConstGenericsWorkaround: AsRef<[bool]> {
-    type MaybeUninitTy: AsMut<[MaybeUninit<bool>]>;
+pub trait MustBeABoolArrayConstGenericsWorkaround: AsRef<[bool]> + AsMut<[bool]> + Sized {
     const NBITS: usize;
+
+    /// Build the array by calling `f` for each bit index, like [core::array::from_fn]
+    fn from_fn(f: impl FnMut(usize) -> bool) -> Self;
 }
 impl<const N: usize> MustBeABoolArrayConstGenericsWorkaround for [bool; N] {
-    type MaybeUninitTy = [MaybeUninit<bool>; N];
     const NBITS: usize = N;
+
+    fn from_fn(f: impl FnMut(usize) -> bool) -> Self {
+        core::array::from_fn(f)
+    }
 }
@@ -2,31 +2,265 @@
 
 use std::borrow::Borrow;
 
-use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
+use proc_macro2::{Delimiter, Spacing, TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
 use syn::{
-    parse::Parser, punctuated::Punctuated, token::Comma, Expr, Field, FnArg, ImplItem, ItemImpl,
-    ItemStruct, Lit, Meta, MetaNameValue, PathArguments, ReturnType, Type,
+    parse::Parser, punctuated::Punctuated, token::Comma, Attribute, Expr, Field, FnArg, Ident,
+    ImplItem, ImplItemFn, ItemImpl, ItemStruct, Lit, Meta, MetaNameValue, Pat, Path, PathArguments,
+    ReturnType, Type,
 };
 
+fn is_bittwiddler_path(p: &Path, attr: &str) -> bool {
+    if p.leading_colon.is_none() && p.segments.len() == 2 {
+        let seg0 = &p.segments[0];
+        let seg1 = &p.segments[1];
+        if seg0.arguments == PathArguments::None && seg1.arguments == PathArguments::None {
+            seg0.ident == "bittwiddler" && seg1.ident == attr
+        } else {
+            false
+        }
+    } else {
+        false
+    }
+}
+
 fn is_bittwiddler_attr(meta: &Meta, attr: &str) -> bool {
     if let Meta::Path(p) = meta {
-        if p.leading_colon.is_none() && p.segments.len() == 2 {
-            let seg0 = &p.segments[0];
-            let seg1 = &p.segments[1];
-            if seg0.arguments == PathArguments::None && seg1.arguments == PathArguments::None {
-                seg0.ident == "bittwiddler" && seg1.ident == attr
-            } else {
-                false
+        is_bittwiddler_path(p, attr)
+    } else {
+        false
+    }
+}
+
+/// Joins all of the `///` comments on an item
+fn doc_string(attrs: &[Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter_map(|attr| {
+            if let Meta::NameValue(nv) = &attr.meta {
+                if nv.path.is_ident("doc") {
+                    if let Expr::Lit(syn::ExprLit {
+                        lit: Lit::Str(x), ..
+                    }) = &nv.value
+                    {
+                        let line = x.value();
+                        return Some(line.strip_prefix(' ').unwrap_or(&line).to_owned());
+                    }
+                }
+            }
+            None
+        })
+        .collect::<Vec<_>>();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+fn option_tokens(x: Option<String>) -> TokenStream {
+    if let Some(x) = x {
+        quote! {::core::option::Option::Some(#x)}
+    } else {
+        quote! {::core::option::Option::None}
+    }
+}
+
+/// Turns a type or expression back into source text for introspection
+///
+/// Unlike [TokenStream]'s `to_string`, this doesn't put spaces between every token,
+/// so e.g. `Vec<u8>` comes out as written rather than as `Vec < u8 >`.
+fn tokens_to_source(tokens: impl ToTokens) -> String {
+    fn write_tokens(tokens: TokenStream, ret: &mut String) {
+        // whether a space is needed before the next identifier or literal
+        let mut after_word = false;
+        let mut op = String::new();
+        for tt in tokens {
+            match tt {
+                TokenTree::Ident(_) | TokenTree::Literal(_) => {
+                    if after_word {
+                        ret.push(' ');
+                    }
+                    ret.push_str(&tt.to_string());
+                    after_word = true;
+                }
+                TokenTree::Group(g) => {
+                    let (open, close) = match g.delimiter() {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::Brace => {
+                            if after_word {
+                                ret.push(' ');
+                            }
+                            ("{ ", " }")
+                        }
+                        Delimiter::None => ("", ""),
+                    };
+                    ret.push_str(open);
+                    write_tokens(g.stream(), ret);
+                    ret.push_str(close);
+                    after_word = true;
+                }
+                TokenTree::Punct(p) => {
+                    op.push(p.as_char());
+                    if p.spacing() == Spacing::Joint && p.as_char() != '\'' {
+                        continue;
+                    }
+                    match op.as_str() {
+                        "," | ";" => {
+                            ret.push_str(&op);
+                            ret.push(' ');
+                        }
+                        "+" | "=" | "->" | "=>" | "==" | "!=" | "&&" | "||" => {
+                            ret.push(' ');
+                            ret.push_str(&op);
+                            ret.push(' ');
+                        }
+                        _ => ret.push_str(&op),
+                    }
+                    op.clear();
+                    after_word = false;
+                }
             }
-        } else {
-            false
         }
+    }
+
+    let mut ret = String::new();
+    write_tokens(tokens.into_token_stream(), &mut ret);
+    ret
+}
+
+/// Does this refer to `self` anywhere?
+fn mentions_self(tokens: TokenStream) -> bool {
+    tokens.into_iter().any(|tt| match tt {
+        TokenTree::Ident(i) => i == "self",
+        TokenTree::Group(g) => mentions_self(g.stream()),
+        _ => false,
+    })
+}
+
+/// Is this `#[bittwiddler::range(...)]` or `#[bittwiddler::values(...)]`?
+fn is_enumeration_attr(attr: &Attribute) -> bool {
+    if let Meta::List(l) = &attr.meta {
+        is_bittwiddler_path(&l.path, "range") || is_bittwiddler_path(&l.path, "values")
     } else {
         false
     }
 }
 
+struct Enumerations {
+    /// Names of all the arguments (not including self), in the order they are declared
+    args: Vec<Ident>,
+    /// Argument names and expressions, in nesting order (function attributes first,
+    /// in the order they are written, and then argument attributes)
+    nesting: Vec<(Ident, Expr)>,
+}
+
+/// Finds the values that each argument of a sublevel/property function should take
+/// when enumerating every instance of it
+///
+/// These can be specified either on the function, e.g. `#[bittwiddler::range(y = 0..4, x = 0..4)]`,
+/// or on the argument itself, e.g. `#[bittwiddler::values([1, 2, 4])] n: u8`.
+/// `range` and `values` are equivalent, and both take anything implementing `IntoIterator`.
+///
+/// Returns `None` if no argument has an expression.
+fn parse_enumerations(impl_fn: &mut ImplItemFn) -> syn::Result<Option<Enumerations>> {
+    let mut args = Vec::new();
+    let mut arg_attr_exprs = Vec::new();
+    // indexed the same way as the argument names, i.e. not counting the receiver
+    let typed_args = impl_fn.sig.inputs.iter_mut().filter_map(|input| {
+        if let FnArg::Typed(pat_ty) = input {
+            Some(pat_ty)
+        } else {
+            None
+        }
+    });
+    for (arg_i, pat_ty) in typed_args.enumerate() {
+        let ident = if let Pat::Ident(pat_ident) = &*pat_ty.pat {
+            pat_ident.ident.clone()
+        } else {
+            format_ident!("_arg{}", arg_i)
+        };
+        for attr in &pat_ty.attrs {
+            if is_enumeration_attr(attr) {
+                let l = attr.meta.require_list()?;
+                arg_attr_exprs.push((ident.clone(), l.parse_args::<Expr>()?));
+            }
+        }
+        pat_ty.attrs.retain(|attr| !is_enumeration_attr(attr));
+        args.push(ident);
+    }
+
+    let mut ret = Vec::new();
+    for attr in &impl_fn.attrs {
+        if is_enumeration_attr(attr) {
+            let l = attr.meta.require_list()?;
+            let exprs = l.parse_args_with(Punctuated::<MetaNameValue, Comma>::parse_terminated)?;
+            for expr in exprs {
+                let ident = expr.path.require_ident()?;
+                if !args.contains(ident) {
+                    return Err(syn::Error::new_spanned(
+                        ident,
+                        format!("no argument named {}", ident),
+                    ));
+                }
+                ret.push((ident.clone(), expr.value));
+            }
+        }
+    }
+    impl_fn.attrs.retain(|attr| !is_enumeration_attr(attr));
+    ret.extend(arg_attr_exprs);
+
+    for (i, (ident, _)) in ret.iter().enumerate() {
+        if ret[..i].iter().any(|(x, _)| x == ident) {
+            return Err(syn::Error::new_spanned(
+                ident,
+                format!("argument {} is enumerated more than once", ident),
+            ));
+        }
+    }
+    if ret.is_empty() {
+        return Ok(None);
+    }
+    if ret.len() != args.len() {
+        return Err(syn::Error::new_spanned(
+            &impl_fn.sig.inputs,
+            "either all or none of the arguments must have a range",
+        ));
+    }
+    Ok(Some(Enumerations { args, nesting: ret }))
+}
+
+/// Nested iterators over every combination of enumerated argument values,
+/// where the first argument in the list changes the slowest
+/// (later ranges can refer to earlier arguments)
+///
+/// `make_item` is evaluated with every argument in scope, but only the innermost one is owned.
+fn nested_enumeration(enumerations: &Enumerations, make_item: TokenStream) -> TokenStream {
+    let (innermost_ident, innermost_expr) = enumerations.nesting.last().unwrap();
+    let mut iter = quote! {
+        ::core::iter::Iterator::map(
+            ::core::iter::IntoIterator::into_iter(#innermost_expr),
+            move |#innermost_ident| #make_item
+        )
+    };
+    for (nest_i, (arg_ident, arg_expr)) in enumerations.nesting.iter().enumerate().rev().skip(1) {
+        // this closure gets called repeatedly, so arguments it captured from
+        // further out need to be cloned before moving them into the next one in
+        let outer_idents = enumerations.nesting[..nest_i].iter().map(|(x, _)| x);
+        iter = quote! {
+            ::core::iter::Iterator::flat_map(
+                ::core::iter::IntoIterator::into_iter(#arg_expr),
+                move |#arg_ident| {
+                    #(let #outer_idents = ::core::clone::Clone::clone(&#outer_idents);)*
+                    #iter
+                }
+            )
+        };
+    }
+    iter
+}
+
 struct NoStdSettings {
     alloc_feature_gate: Option<String>,
 }
@@ -117,11 +351,11 @@ pub fn bittwiddler_hierarchy_level(attr: TokenStream, item: TokenStream) -> Toke
             fn to_human_string(&self) -> ::bittwiddler_core::prelude::CowReexport<'static, ::core::primitive::str> {
                 ::core::convert::Into::into("")
             }
-            fn from_human_string(s: &str) -> ::core::result::Result<Self, ()>
+            fn from_human_string(s: &str) -> ::core::result::Result<Self, ::bittwiddler_core::prelude::StringConvError>
             where
                 Self: Sized
             {
-                ::core::result::Result::Err(())
+                ::core::result::Result::Err(::bittwiddler_core::prelude::StringConvError::new(s))
             }
         }
 
@@ -161,6 +395,7 @@ pub fn bittwiddler_properties(attr: TokenStream, item: TokenStream) -> TokenStre
         panic!("Requrires impl on a path");
     };
     let automagic_trait_id = format_ident!("{}AutomagicRequiredFunctions", target_ty_ident);
+    let target_ty_str = tokens_to_source(target_ty);
 
     let mut prop_idx = 0usize;
     let mut sublevel_idx = 0usize;
@@ -171,6 +406,9 @@ pub fn bittwiddler_properties(attr: TokenStream, item: TokenStream) -> TokenStre
     let mut automagic_trait_fns = Vec::new();
     let mut iter_all_subfields = Vec::new();
     let mut iter_all_sublevels = Vec::new();
+    let mut fields_schema = Vec::new();
+    let mut sublevels_schema = Vec::new();
+    let mut arg_values_fns = Vec::new();
     for item in &mut impl_inp.items {
         if let ImplItem::Fn(impl_fn) = item {
             let mut is_prop = false;
@@ -190,6 +428,20 @@ pub fn bittwiddler_properties(attr: TokenStream, item: TokenStream) -> TokenStre
                 }
             }
 
+            let mut is_var_width = false;
+            for (attr_i, attr) in impl_fn.attrs.iter().enumerate() {
+                if is_bittwiddler_attr(&attr.meta, "var_width") {
+                    impl_fn.attrs.remove(attr_i);
+                    is_var_width = true;
+                    break;
+                }
+            }
+
+            let enumerations = match parse_enumerations(impl_fn) {
+                Ok(x) => x,
+                Err(e) => return e.to_compile_error(),
+            };
+
             let mut num_args = impl_fn.sig.inputs.len();
             let mut has_self = false;
             if num_args >= 1 {
@@ -214,26 +466,163 @@ pub fn bittwiddler_properties(attr: TokenStream, item: TokenStream) -> TokenStre
             }
 
             // construct a specific sublevel
-            let args_parse_bits = (0..num_args).map(|arg_i| {
+            let arg_names = impl_fn
+                .sig
+                .inputs
+                .iter()
+                .filter_map(|arg| {
+                    if let FnArg::Typed(arg) = arg {
+                        Some(arg)
+                    } else {
+                        None
+                    }
+                })
+                .enumerate()
+                .map(|(arg_i, arg)| {
+                    let name = if let Pat::Ident(pat) = &*arg.pat {
+                        pat.ident.to_string()
+                    } else {
+                        arg_i.to_string()
+                    };
+                    (name, &arg.ty)
+                })
+                .collect::<Vec<_>>();
+            let args_parse_bits = arg_names.iter().enumerate().map(|(arg_i, (arg_name, _))| {
                 quote! {
-                    ::bittwiddler_core::prelude::StatePiece::from_human_string(
-                        &_params[#arg_i]
+                    ::core::result::Result::map_err(
+                        ::bittwiddler_core::prelude::StatePiece::from_human_string(&_params[#arg_i]),
+                        |e| e.for_arg(#arg_name)
                     )?
                 }
             });
-            let make_obj = if has_self {
+            let call = if has_self {
                 quote! {self.#ident(#(#args_parse_bits),*)}
             } else {
                 quote! {Self::#ident(#(#args_parse_bits),*)}
             };
+
+            // introspection
+            let doc = option_tokens(doc_string(&impl_fn.attrs));
+            let args_schema = arg_names
+                .iter()
+                .enumerate()
+                .map(|(arg_i, (arg_name, arg_ty))| {
+                    let ty_str = tokens_to_source(arg_ty);
+                    let values = enumerations.as_ref().and_then(|enumerations| {
+                        let ident = &enumerations.args[arg_i];
+                        enumerations
+                            .nesting
+                            .iter()
+                            .find(|(x, _)| x == ident)
+                            .map(|(_, expr)| tokens_to_source(expr))
+                    });
+                    let values = option_tokens(values);
+                    quote! {
+                        ::bittwiddler_core::prelude::ArgSchema {
+                            name: #arg_name,
+                            ty: #ty_str,
+                            values: #values,
+                        }
+                    }
+                });
+            let name_str = ident.to_string();
+            // this needs to be impl-ed by user, unless every argument has a range
+            let needs_automagic = is_conditional || (num_args > 0 && enumerations.is_none());
+            let arg_values_iter = if needs_automagic {
+                None
+            } else if let Some(enumerations) = &enumerations {
+                let exprs_mention_self = enumerations
+                    .nesting
+                    .iter()
+                    .any(|(_, expr)| mentions_self(expr.to_token_stream()));
+                if exprs_mention_self {
+                    None
+                } else {
+                    let args = &enumerations.args;
+                    Some(nested_enumeration(
+                        enumerations,
+                        quote! {::bittwiddler_core::prelude::_arg_values_item([#(&#args),*])},
+                    ))
+                }
+            } else {
+                Some(quote! {
+                    ::core::iter::once(::bittwiddler_core::prelude::_arg_values_item([]))
+                })
+            };
+            let arg_values = if let Some(iter) = arg_values_iter {
+                let arg_values_fn_ident = format_ident!("_bittwiddler_arg_values_{}", ident);
+                arg_values_fns.push(quote! {
+                    fn #arg_values_fn_ident() -> ::bittwiddler_core::prelude::ArgValuesIter {
+                        ::bittwiddler_core::prelude::BoxReexport::new(#iter)
+                    }
+                });
+                quote! {::core::option::Option::Some(<#target_ty>::#arg_values_fn_ident)}
+            } else {
+                quote! {::core::option::Option::None}
+            };
+            if is_prop {
+                let accessor_str = tokens_to_source(output_ty);
+                let (nbits, leaf) = if is_var_width {
+                    (
+                        quote! {::core::option::Option::None},
+                        quote! {::bittwiddler_core::prelude::_opaque_leaf_schema},
+                    )
+                } else {
+                    (
+                        quote! {::core::option::Option::Some(::bittwiddler_core::prelude::_accessor_nbits::<#output_ty>())},
+                        quote! {::bittwiddler_core::prelude::_accessor_leaf_schema::<#output_ty>},
+                    )
+                };
+                fields_schema.push(quote! {
+                    ::bittwiddler_core::prelude::FieldSchema {
+                        name: #name_str,
+                        doc: #doc,
+                        args: &[#(#args_schema),*],
+                        conditional: #is_conditional,
+                        arg_values: #arg_values,
+                        accessor: #accessor_str,
+                        nbits: #nbits,
+                        leaf: #leaf,
+                    }
+                });
+            } else {
+                sublevels_schema.push(quote! {
+                    ::bittwiddler_core::prelude::SublevelSchema {
+                        name: #name_str,
+                        doc: #doc,
+                        args: &[#(#args_schema),*],
+                        conditional: #is_conditional,
+                        arg_values: #arg_values,
+                        level: <#output_ty as ::bittwiddler_core::prelude::HumanLevelWithSchema>::_human_schema,
+                    }
+                });
+            }
+
+            let make_obj = quote! {{
+                if _params.len() != #num_args {
+                    return ::core::result::Result::Err(
+                        ::bittwiddler_core::prelude::StringConvError::wrong_arg_count(_params, #num_args)
+                    );
+                }
+                #call
+            }};
             let thing_idx = if is_prop {
                 quote! {#prop_idx}
             } else {
                 quote! {#sublevel_idx}
             };
+            // runtime-width properties go through a different wrapper to become PropertyAccessorDyn
+            let wrap_prop = |obj: TokenStream| {
+                if is_var_width {
+                    quote! {::bittwiddler_core::prelude::VarWidthDyn(#obj)}
+                } else {
+                    quote! {::bittwiddler_core::prelude::BoxReexport::new(#obj)}
+                }
+            };
             if is_prop {
+                let wrapped_obj = wrap_prop(make_obj.clone());
                 make_subfields.push(quote! {
-                    #thing_idx => ::core::result::Result::Ok(::bittwiddler_core::prelude::BoxReexport::new(::bittwiddler_core::prelude::BoxReexport::new(#make_obj))),
+                    #thing_idx => ::core::result::Result::Ok(::bittwiddler_core::prelude::BoxReexport::new(#wrapped_obj)),
                 });
             } else {
                 make_sublevels.push(quote! {
@@ -241,8 +630,7 @@ pub fn bittwiddler_properties(attr: TokenStream, item: TokenStream) -> TokenStre
                 });
             }
 
-            // this needs to be impl-ed by user
-            if is_conditional || num_args > 0 {
+            if needs_automagic {
                 let automagic_fn_ident = format_ident!("_automagic_construct_all_{}", ident);
                 automagic_trait_fns.push(quote! {
                     fn #automagic_fn_ident(&self) -> impl ::core::iter::Iterator<Item = #output_ty>;
@@ -251,9 +639,10 @@ pub fn bittwiddler_properties(attr: TokenStream, item: TokenStream) -> TokenStre
 
             // construct *all* sublevels
             let coerce = if is_prop {
+                let wrapped_obj = wrap_prop(quote! {obj});
                 quote! {
                     ::bittwiddler_core::prelude::BoxReexport::new(
-                        ::bittwiddler_core::prelude::BoxReexport::new(obj),
+                        #wrapped_obj,
                     )
                         as ::bittwiddler_core::prelude::BoxReexport<
                             dyn ::bittwiddler_core::prelude::PropertyAccessorDyn,
@@ -267,7 +656,7 @@ pub fn bittwiddler_properties(attr: TokenStream, item: TokenStream) -> TokenStre
                     >
                 }
             };
-            let make_all_of_this_prop = if is_conditional || num_args > 0 {
+            let make_all_of_this_prop = if needs_automagic {
                 let automagic_fn_ident = format_ident!("_automagic_construct_all_{}", ident);
                 quote! {
                     #thing_idx => ::bittwiddler_core::prelude::BoxReexport::new(
@@ -276,10 +665,32 @@ pub fn bittwiddler_properties(attr: TokenStream, item: TokenStream) -> TokenStre
                         })
                     ),
                 }
+            } else if let Some(enumerations) = &enumerations {
+                let (innermost_ident, _) = enumerations.nesting.last().unwrap();
+                let call_args = enumerations.args.iter().map(|ident| {
+                    if ident == innermost_ident {
+                        quote! {#ident}
+                    } else {
+                        quote! {::core::clone::Clone::clone(&#ident)}
+                    }
+                });
+                let call = if has_self {
+                    quote! {self.#ident(#(#call_args),*)}
+                } else {
+                    quote! {Self::#ident(#(#call_args),*)}
+                };
+                let iter = nested_enumeration(enumerations, call);
+                quote! {
+                    #thing_idx => ::bittwiddler_core::prelude::BoxReexport::new(
+                        ::core::iter::Iterator::map(#iter, |obj| {
+                            #coerce
+                        })
+                    ),
+                }
             } else {
                 quote! {
                     #thing_idx => ::bittwiddler_core::prelude::BoxReexport::new(::core::iter::IntoIterator::into_iter([{
-                        let obj = #make_obj;
+                        let obj = #call;
                         #coerce
                     }])),
                 }
@@ -303,6 +714,9 @@ pub fn bittwiddler_properties(attr: TokenStream, item: TokenStream) -> TokenStre
 
         #alloc_feature_gate
         impl #generics ::bittwiddler_core::prelude::HumanLevelDynamicAccessor for #target_ty {
+            fn _human_level_schema(&self) -> &'static ::bittwiddler_core::prelude::LevelSchema {
+                <Self as ::bittwiddler_core::prelude::HumanLevelWithSchema>::_human_schema()
+            }
             fn _human_fields(&self) -> &'static [&'static ::core::primitive::str] {
                 &[
                     #(#fields_strs),*
@@ -318,7 +732,7 @@ pub fn bittwiddler_properties(attr: TokenStream, item: TokenStream) -> TokenStre
                 &self,
                 idx: ::core::primitive::usize,
                 _params: &[&::core::primitive::str]
-            ) -> ::core::result::Result<::bittwiddler_core::prelude::BoxReexport<dyn ::bittwiddler_core::prelude::PropertyAccessorDyn>, ()> {
+            ) -> ::core::result::Result<::bittwiddler_core::prelude::BoxReexport<dyn ::bittwiddler_core::prelude::PropertyAccessorDyn>, ::bittwiddler_core::prelude::StringConvError> {
                 match idx {
                     #(#make_subfields)*
                     _ => unreachable!()
@@ -344,7 +758,7 @@ pub fn bittwiddler_properties(attr: TokenStream, item: TokenStream) -> TokenStre
                 &self,
                 idx: ::core::primitive::usize,
                 _params: &[&::core::primitive::str],
-            ) -> ::core::result::Result<::bittwiddler_core::prelude::BoxReexport<dyn ::bittwiddler_core::prelude::HumanLevelDynamicAccessor>, ()> {
+            ) -> ::core::result::Result<::bittwiddler_core::prelude::BoxReexport<dyn ::bittwiddler_core::prelude::HumanLevelDynamicAccessor>, ::bittwiddler_core::prelude::StringConvError> {
                 match idx {
                     #(#make_sublevels)*
                     _ => unreachable!()
@@ -367,9 +781,503 @@ pub fn bittwiddler_properties(attr: TokenStream, item: TokenStream) -> TokenStre
             }
         }
 
+        #alloc_feature_gate
+        impl #generics ::bittwiddler_core::prelude::HumanLevelWithSchema for #target_ty {
+            fn _human_schema() -> &'static ::bittwiddler_core::prelude::LevelSchema {
+                const SCHEMA: ::bittwiddler_core::prelude::LevelSchema = ::bittwiddler_core::prelude::LevelSchema {
+                    name: #target_ty_str,
+                    sublevels: &[#(#sublevels_schema),*],
+                    fields: &[#(#fields_schema),*],
+                };
+                &SCHEMA
+            }
+        }
+
+        #alloc_feature_gate
+        impl #generics #target_ty {
+            #(#arg_values_fns)*
+        }
+
         #alloc_feature_gate
         trait #automagic_trait_id {
             #(#automagic_trait_fns)*
         }
     }
 }
+
+/// Settings from `#[bittwiddler(...)]` on a struct or field deriving `PropertyLeaf`
+#[derive(Default)]
+struct LeafAttrs {
+    bits: Option<usize>,
+    offset: Option<usize>,
+    no_default: bool,
+    name_values: Punctuated<MetaNameValue, Comma>,
+}
+
+fn parse_leaf_attrs(attrs: &[Attribute]) -> syn::Result<LeafAttrs> {
+    let mut ret = LeafAttrs::default();
+    for attr in attrs {
+        if !attr.path().is_ident("bittwiddler") {
+            continue;
+        }
+        let metas = attr.parse_args_with(Punctuated::<Meta, Comma>::parse_terminated)?;
+        for meta in metas {
+            match meta {
+                Meta::Path(p) if p.is_ident("no_default") => ret.no_default = true,
+                Meta::NameValue(nv) if nv.path.is_ident("bits") || nv.path.is_ident("offset") => {
+                    let val = if let Expr::Lit(syn::ExprLit {
+                        lit: Lit::Int(x), ..
+                    }) = &nv.value
+                    {
+                        x.base10_parse::<usize>()?
+                    } else {
+                        return Err(syn::Error::new_spanned(
+                            &nv.value,
+                            "expected an integer literal",
+                        ));
+                    };
+                    if nv.path.is_ident("bits") {
+                        ret.bits = Some(val);
+                    } else {
+                        ret.offset = Some(val);
+                    }
+                }
+                Meta::NameValue(nv) if nv.path.is_ident("alloc_feature_gate") => {
+                    ret.name_values.push(nv)
+                }
+                _ => return Err(syn::Error::new_spanned(meta, "unknown bittwiddler setting")),
+            }
+        }
+    }
+    Ok(ret)
+}
+
+pub fn derive_property_leaf(item: TokenStream) -> TokenStream {
+    match derive_property_leaf_impl(item) {
+        Ok(x) => x,
+        Err(e) => e.to_compile_error(),
+    }
+}
+
+fn derive_property_leaf_impl(item: TokenStream) -> syn::Result<TokenStream> {
+    let struct_inp = syn::parse2::<ItemStruct>(item)?;
+    let struct_attrs = parse_leaf_attrs(&struct_inp.attrs)?;
+    if struct_attrs.offset.is_some() {
+        return Err(syn::Error::new_spanned(
+            &struct_inp.ident,
+            "offset can only be specified on fields",
+        ));
+    }
+
+    let settings = parse_no_std_settings(&struct_attrs.name_values);
+    let alloc_feature_gate = if let Some(alloc_feature) = settings.alloc_feature_gate {
+        quote! {
+            #[cfg(feature = #alloc_feature)]
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    let ident = &struct_inp.ident;
+    let fields_are_named = matches!(struct_inp.fields, syn::Fields::Named(_));
+
+    // (name, member, type, offset, width)
+    let mut fields = Vec::new();
+    let mut next_offset = 0;
+    for (field_i, f) in struct_inp.fields.iter().enumerate() {
+        let field_attrs = parse_leaf_attrs(&f.attrs)?;
+        if field_attrs.no_default || !field_attrs.name_values.is_empty() {
+            return Err(syn::Error::new_spanned(
+                f,
+                "only bits and offset can be specified on fields",
+            ));
+        }
+        let is_bool =
+            matches!(&f.ty, Type::Path(p) if p.qself.is_none() && p.path.is_ident("bool"));
+        let width = match field_attrs.bits {
+            Some(x) => x,
+            None if is_bool => 1,
+            None => {
+                return Err(syn::Error::new_spanned(
+                    f,
+                    "fields other than bool must specify their number of bits",
+                ))
+            }
+        };
+        let offset = field_attrs.offset.unwrap_or(next_offset);
+        next_offset = offset + width;
+
+        let (name, member) = if fields_are_named {
+            let ident = f.ident.as_ref().unwrap();
+            (ident.to_string(), quote! {#ident})
+        } else {
+            let idx = syn::Index::from(field_i);
+            (field_i.to_string(), quote! {#idx})
+        };
+        fields.push((name, member, &f.ty, offset, width));
+    }
+
+    let used_bits = fields.iter().map(|f| f.3 + f.4).max().unwrap_or(0);
+    let nbits = struct_attrs.bits.unwrap_or(used_bits);
+    if nbits < used_bits {
+        return Err(syn::Error::new_spanned(
+            ident,
+            format!(
+                "fields need {} bits but only {} were specified",
+                used_bits, nbits
+            ),
+        ));
+    }
+    for (i, a) in fields.iter().enumerate() {
+        for b in &fields[..i] {
+            if a.3 < b.3 + b.4 && b.3 < a.3 + a.4 {
+                return Err(syn::Error::new_spanned(
+                    a.2,
+                    format!("field {} overlaps field {}", a.0, b.0),
+                ));
+            }
+        }
+    }
+
+    let from_bits_fields = fields.iter().map(|(_, member, ty, offset, width)| {
+        quote! {
+            #member: <#ty as ::bittwiddler_core::prelude::PropertyLeaf<[::core::primitive::bool; #width]>>::from_bits(
+                &::core::array::from_fn(|i| bits[#offset + i])
+            ),
+        }
+    });
+    let checked_from_bits_fields = fields.iter().map(|(_, member, ty, offset, width)| {
+        quote! {
+            #member: match <#ty as ::bittwiddler_core::prelude::PropertyLeaf<[::core::primitive::bool; #width]>>::checked_from_bits(
+                &::core::array::from_fn(|i| bits[#offset + i])
+            ) {
+                ::core::result::Result::Ok(x) => x,
+                ::core::result::Result::Err(_) => {
+                    return ::core::result::Result::Err(::bittwiddler_core::prelude::UnrecognizedBitsError { bits: *bits });
+                }
+            },
+        }
+    });
+    let to_bits_fields = fields.iter().map(|(_, member, ty, offset, width)| {
+        quote! {
+            ret[#offset..#offset + #width].copy_from_slice(
+                &<#ty as ::bittwiddler_core::prelude::PropertyLeaf<[::core::primitive::bool; #width]>>::to_bits(&self.#member)
+            );
+        }
+    });
+
+    let default_impl = if struct_attrs.no_default {
+        TokenStream::new()
+    } else {
+        let default_fields = fields.iter().map(|(_, member, ..)| {
+            quote! {#member: ::core::default::Default::default(),}
+        });
+        quote! {
+            impl ::core::default::Default for #ident {
+                fn default() -> Self {
+                    Self {
+                        #(#default_fields)*
+                    }
+                }
+            }
+        }
+    };
+
+    let string_conv_bounds = fields.iter().map(|(_, _, ty, _, width)| {
+        quote! {
+            #ty: ::bittwiddler_core::prelude::PropertyLeafWithStringConv<[::core::primitive::bool; #width], A>,
+        }
+    });
+    let to_string_fields = fields.iter().map(|(name, member, ty, _, width)| {
+        quote! {
+            (#name, <#ty as ::bittwiddler_core::prelude::PropertyLeafWithStringConv<[::core::primitive::bool; #width], A>>::to_string(&self.#member, accessor)),
+        }
+    });
+    let from_string_vars = (0..fields.len())
+        .map(|i| format_ident!("_field_{}", i))
+        .collect::<Vec<_>>();
+    let from_string_matches = fields.iter().zip(&from_string_vars).map(|((name, _, ty, _, width), var)| {
+        quote! {
+            #name if #var.is_none() => {
+                #var = ::core::option::Option::Some(
+                    <#ty as ::bittwiddler_core::prelude::PropertyLeafWithStringConv<[::core::primitive::bool; #width], A>>::from_string(val, accessor)?
+                );
+            }
+        }
+    });
+    let from_string_fields = fields
+        .iter()
+        .zip(&from_string_vars)
+        .map(|((_, member, ..), var)| {
+            quote! {
+                #member: #var.ok_or_else(err)?,
+            }
+        });
+
+    let struct_doc = option_tokens(doc_string(&struct_inp.attrs));
+    let sub_leaf_schemas = fields.iter().map(|(name, _, ty, offset, width)| {
+        quote! {
+            ::bittwiddler_core::prelude::SubLeafSchema {
+                name: #name,
+                offset: #offset,
+                nbits: #width,
+                leaf: ::bittwiddler_core::prelude::_leaf_schema::<[::core::primitive::bool; #width], #ty>,
+            }
+        }
+    });
+
+    let expected_form = format!(
+        "{{{}}}",
+        fields
+            .iter()
+            .map(|(name, ..)| format!("{}=...", name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    Ok(quote! {
+        impl ::bittwiddler_core::prelude::PropertyLeaf<[::core::primitive::bool; #nbits]> for #ident {
+            fn from_bits(bits: &[::core::primitive::bool; #nbits]) -> Self {
+                Self {
+                    #(#from_bits_fields)*
+                }
+            }
+
+            fn to_bits(&self) -> [::core::primitive::bool; #nbits] {
+                let mut ret = [false; #nbits];
+                #(#to_bits_fields)*
+                ret
+            }
+
+            fn checked_from_bits(
+                bits: &[::core::primitive::bool; #nbits]
+            ) -> ::core::result::Result<Self, ::bittwiddler_core::prelude::UnrecognizedBitsError<[::core::primitive::bool; #nbits]>> {
+                ::core::result::Result::Ok(Self {
+                    #(#checked_from_bits_fields)*
+                })
+            }
+
+            const SCHEMA: ::bittwiddler_core::prelude::LeafSchema = ::bittwiddler_core::prelude::LeafSchema {
+                doc: #struct_doc,
+                kind: ::bittwiddler_core::prelude::LeafKind::Composite(&[#(#sub_leaf_schemas),*]),
+            };
+        }
+
+        #default_impl
+
+        #alloc_feature_gate
+        impl<A: ::bittwiddler_core::prelude::PropertyAccessor> ::bittwiddler_core::prelude::PropertyLeafWithStringConv<[::core::primitive::bool; #nbits], A> for #ident
+        where
+            #(#string_conv_bounds)*
+        {
+            fn to_string(&self, accessor: &A) -> ::bittwiddler_core::prelude::CowReexport<'static, ::core::primitive::str> {
+                ::bittwiddler_core::prelude::_composite_to_string(&[
+                    #(#to_string_fields)*
+                ])
+            }
+
+            fn from_string(s: &str, accessor: &A) -> ::core::result::Result<Self, ::bittwiddler_core::prelude::StringConvError> {
+                let err = || ::bittwiddler_core::prelude::StringConvError::new(s).expected(#expected_form);
+                #(let mut #from_string_vars = ::core::option::Option::None;)*
+                for (name, val) in ::bittwiddler_core::prelude::_composite_split(s).ok_or_else(err)? {
+                    match name {
+                        #(#from_string_matches)*
+                        _ => return ::core::result::Result::Err(err()),
+                    }
+                }
+                ::core::result::Result::Ok(Self {
+                    #(#from_string_fields)*
+                })
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranges() {
+        let outp = bittwiddler_properties(
+            TokenStream::new(),
+            quote! {
+                impl Chip {
+                    #[bittwiddler::range(y = 0..Self::H, x = 0..y)]
+                    pub fn tile(x: u8, y: u8) -> Tile {
+                        Tile { x, y }
+                    }
+                    #[bittwiddler::property]
+                    pub fn lut(&self, #[bittwiddler::values([0, 2])] n: u8) -> Lut {
+                        Lut { n }
+                    }
+                    pub fn irregular(&self, n: u8) -> Irregular {
+                        Irregular { n }
+                    }
+                    #[bittwiddler::property]
+                    pub fn unnamed(&self, #[bittwiddler::values([1])] _: u8) -> Unnamed {
+                        Unnamed
+                    }
+                    #[bittwiddler::range(a = names(), b = names_after(&a), c = names_after(&b))]
+                    pub fn wire(a: String, b: String, c: String) -> Wire {
+                        Wire { a, b, c }
+                    }
+                }
+            },
+        )
+        .to_string();
+
+        // attributes must be removed
+        assert!(!outp.contains("bittwiddler :: range"));
+        assert!(!outp.contains("bittwiddler :: values"));
+        assert!(outp.contains("pub fn lut (& self , n : u8) -> Lut"));
+        // y is outermost, and x's range can refer to it
+        assert!(outp.contains("flat_map (:: core :: iter :: IntoIterator :: into_iter (0 .. Self :: H) , move | y | { :: core :: iter :: Iterator :: map (:: core :: iter :: IntoIterator :: into_iter (0 .. y) , move | x | Self :: tile (x , :: core :: clone :: Clone :: clone (& y))) })"));
+        assert!(outp.contains("into_iter ([0 , 2]) , move | n | self . lut (n)"));
+        // unnamed arguments are numbered without counting self
+        assert!(outp.contains("into_iter ([1]) , move | _arg0 | self . unnamed (_arg0)"));
+        // outer arguments are cloned before moving them into the next closure in
+        assert!(outp.contains("move | b | { let a = :: core :: clone :: Clone :: clone (& a) ; :: core :: iter :: Iterator :: map (:: core :: iter :: IntoIterator :: into_iter (names_after (& b)) , move | c | Self :: wire (:: core :: clone :: Clone :: clone (& a) , :: core :: clone :: Clone :: clone (& b) , c))"));
+        // only the function without a range needs the trait
+        assert!(!outp.contains("_automagic_construct_all_tile"));
+        assert!(!outp.contains("_automagic_construct_all_lut"));
+        assert!(outp.contains("fn _automagic_construct_all_irregular"));
+        // introspection records the source of the ranges, and can enumerate them without self
+        assert!(outp.contains("name : \"n\" , ty : \"u8\" , values : :: core :: option :: Option :: Some (\"[0, 2]\")"));
+        assert!(outp.contains("name : \"y\" , ty : \"u8\" , values : :: core :: option :: Option :: Some (\"0..Self::H\")"));
+        assert!(outp.contains("name : \"a\" , ty : \"String\" , values : :: core :: option :: Option :: Some (\"names()\")"));
+        assert!(outp.contains("arg_values : :: core :: option :: Option :: Some (< Chip > :: _bittwiddler_arg_values_tile)"));
+        assert!(outp.contains("fn _bittwiddler_arg_values_lut ()"));
+        assert!(outp.contains("_arg_values_item ([& x , & y])"));
+        assert!(!outp.contains("_bittwiddler_arg_values_irregular"));
+    }
+
+    #[test]
+    fn test_tokens_to_source() {
+        assert_eq!(tokens_to_source(quote! {Vec < u8 >}), "Vec<u8>");
+        assert_eq!(tokens_to_source(quote! {&'static str}), "&'static str");
+        assert_eq!(tokens_to_source(quote! {[bool; 4]}), "[bool; 4]");
+        assert_eq!(
+            tokens_to_source(quote! {Box<dyn Fn(u8) -> Vec<Vec<u8>> + Send>}),
+            "Box<dyn Fn(u8) -> Vec<Vec<u8>> + Send>"
+        );
+        assert_eq!(
+            tokens_to_source(quote! {(0..=x).step_by(2)}),
+            "(0..=x).step_by(2)"
+        );
+        assert_eq!(
+            tokens_to_source(quote! {crate::tiles::Tile}),
+            "crate::tiles::Tile"
+        );
+    }
+
+    #[test]
+    fn test_ranges_errors() {
+        let outp = bittwiddler_properties(
+            TokenStream::new(),
+            quote! {
+                impl Chip {
+                    #[bittwiddler::range(x = 0..4)]
+                    pub fn tile(x: u8, y: u8) -> Tile {
+                        Tile { x, y }
+                    }
+                }
+            },
+        )
+        .to_string();
+        assert!(outp.contains("either all or none of the arguments must have a range"));
+
+        let outp = bittwiddler_properties(
+            TokenStream::new(),
+            quote! {
+                impl Chip {
+                    #[bittwiddler::range(z = 0..4)]
+                    pub fn tile(x: u8) -> Tile {
+                        Tile { x }
+                    }
+                }
+            },
+        )
+        .to_string();
+        assert!(outp.contains("no argument named z"));
+
+        let outp = bittwiddler_properties(
+            TokenStream::new(),
+            quote! {
+                impl Chip {
+                    #[bittwiddler::range(x = 0..4)]
+                    pub fn tile(#[bittwiddler::range(0..4)] x: u8) -> Tile {
+                        Tile { x }
+                    }
+                }
+            },
+        )
+        .to_string();
+        assert!(outp.contains("argument x is enumerated more than once"));
+    }
+
+    #[test]
+    fn test_derive_property_leaf() {
+        let outp = derive_property_leaf(quote! {
+            #[bittwiddler(bits = 8, alloc_feature_gate = "alloc")]
+            struct Pll {
+                #[bittwiddler(bits = 3)]
+                div: Thermometer<3>,
+                #[bittwiddler(offset = 4, bits = 2)]
+                phase: OneHot<2>,
+                en: bool,
+            }
+        })
+        .to_string();
+
+        assert!(outp.contains("PropertyLeaf < [:: core :: primitive :: bool ; 8usize] > for Pll"));
+        assert!(outp.contains("bits [0usize + i]"));
+        assert!(outp.contains("bits [4usize + i]"));
+        // fields without an offset follow the previous one
+        assert!(outp.contains("ret [6usize .. 6usize + 1usize]"));
+        assert!(outp.contains("impl :: core :: default :: Default for Pll"));
+        assert!(outp.contains("# [cfg (feature = \"alloc\")] impl < A"));
+        assert!(outp.contains("(\"phase\" ,"));
+
+        let outp = derive_property_leaf(quote! {
+            #[bittwiddler(no_default)]
+            struct Pair(#[bittwiddler(bits = 2)] Gray<u8>, bool);
+        })
+        .to_string();
+        assert!(outp.contains("PropertyLeaf < [:: core :: primitive :: bool ; 3usize] > for Pair"));
+        assert!(outp.contains("1 : < bool as"));
+        assert!(!outp.contains("Default for Pair"));
+    }
+
+    #[test]
+    fn test_derive_property_leaf_errors() {
+        let outp = derive_property_leaf(quote! {
+            struct Pll {
+                div: u8,
+            }
+        })
+        .to_string();
+        assert!(outp.contains("fields other than bool must specify their number of bits"));
+
+        let outp = derive_property_leaf(quote! {
+            struct Pll {
+                #[bittwiddler(bits = 3)]
+                div: u8,
+                #[bittwiddler(offset = 2)]
+                en: bool,
+            }
+        })
+        .to_string();
+        assert!(outp.contains("field en overlaps field div"));
+
+        let outp = derive_property_leaf(quote! {
+            #[bittwiddler(bits = 2)]
+            struct Pll {
+                #[bittwiddler(bits = 3)]
+                div: u8,
+            }
+        })
+        .to_string();
+        assert!(outp.contains("fields need 3 bits but only 2 were specified"));
+    }
+}
@@ -8,6 +8,11 @@ pub struct Settings {
     pub enable_no_std: bool,
     pub alloc_feature_gate: Option<String>,
     pub emit_string_formatter: bool,
+    /// Also emit `PropertyLeaf::checked_from_bits` and `TryFrom`, returning an error for unknown patterns
+    ///
+    /// If there is no catchall, `from_bits` will then panic on unknown patterns rather than
+    /// requiring the patterns to be exhaustive.
+    pub emit_try_from_bits: bool,
 }
 impl Default for Settings {
     fn default() -> Self {
@@ -15,6 +20,7 @@ impl Default for Settings {
             enable_no_std: false,
             alloc_feature_gate: None,
             emit_string_formatter: true,
+            emit_try_from_bits: false,
         }
     }
 }
@@ -33,6 +39,11 @@ pub fn emit(prop: &BitProperty, settings: &Settings) -> TokenStream {
     } else {
         TokenStream::new()
     };
+    let prop_doc = if let Some(doc) = &prop.documentation {
+        quote! {::core::option::Option::Some(#doc)}
+    } else {
+        quote! {::core::option::Option::None}
+    };
     let prop_name_ident = Ident::new(&prop.name, Span::call_site());
     let prop_var_idents = prop
         .variants
@@ -48,9 +59,12 @@ pub fn emit(prop: &BitProperty, settings: &Settings) -> TokenStream {
 
     let mut prop_variants_decl = Vec::new();
     let mut var_decode_matches = Vec::new();
+    let mut var_try_decode_matches = Vec::new();
     let mut var_decode_str_matches = Vec::new();
     let mut var_encode_matches = Vec::new();
     let mut var_encode_str_matches = Vec::new();
+    let mut var_expected_strs = Vec::new();
+    let mut var_schemas = Vec::new();
     for (i, var) in prop
         .variants
         .iter()
@@ -74,6 +88,28 @@ pub fn emit(prop: &BitProperty, settings: &Settings) -> TokenStream {
             prop_variants_decl.push(quote! {#documentation #var_ident})
         }
 
+        // introspection
+        let var_doc = if let Some(doc) = &var.documentation {
+            quote! {::core::option::Option::Some(#doc)}
+        } else {
+            quote! {::core::option::Option::None}
+        };
+        let var_pattern = if i == prop.variants.len() {
+            quote! {::core::option::Option::None}
+        } else {
+            let pattern = &var.pattern;
+            quote! {::core::option::Option::Some(#pattern)}
+        };
+        let keeps_bits = var.keep_bits;
+        var_schemas.push(quote! {
+            ::bittwiddler_core::prelude::VariantSchema {
+                name: #field_name_str,
+                doc: #var_doc,
+                pattern: #var_pattern,
+                keeps_bits: #keeps_bits,
+            }
+        });
+
         // decode
         let match_pat = if i == prop.variants.len() {
             quote! {_}
@@ -89,18 +125,27 @@ pub fn emit(prop: &BitProperty, settings: &Settings) -> TokenStream {
 
         if var.keep_bits {
             var_decode_matches.push(quote! { #match_pat => Self::#var_ident(*bits) });
+            var_try_decode_matches
+                .push(quote! { #match_pat => ::core::result::Result::Ok(Self::#var_ident(*bits)) });
         } else {
             var_decode_matches.push(quote! { #match_pat => Self::#var_ident });
+            var_try_decode_matches
+                .push(quote! { #match_pat => ::core::result::Result::Ok(Self::#var_ident) });
         }
 
         // decode from string
+        if var.keep_bits {
+            var_expected_strs.push(format!("{}({} bits)", field_name_str, num_bits));
+        } else {
+            var_expected_strs.push(field_name_str.clone());
+        }
         if var.keep_bits {
             var_decode_str_matches.push(quote! { #field_name_str => {
                 let mut bits = [false; #num_bits];
                 let mut chars = ::core::primitive::str::chars(_bits_s);
                 for i in 0..#num_bits {
                     let b = ::core::iter::Iterator::next(&mut chars);
-                    let b = ::core::option::Option::unwrap(b);
+                    let b = ::core::option::Option::ok_or_else(b, err)?;
                     match b {
                         '0' => {
                             bits[i] = false;
@@ -109,15 +154,15 @@ pub fn emit(prop: &BitProperty, settings: &Settings) -> TokenStream {
                             bits[i] = true;
                         }
                         _ => {
-                            return ::core::result::Result::Err(());
+                            return ::core::result::Result::Err(err());
                         }
                     }
                 }
                 if ::core::iter::Iterator::next(&mut chars) != ::core::option::Option::Some(')') {
-                    return ::core::result::Result::Err(());
+                    return ::core::result::Result::Err(err());
                 }
                 if ::core::iter::Iterator::next(&mut chars) != ::core::option::Option::None {
-                    return ::core::result::Result::Err(());
+                    return ::core::result::Result::Err(err());
                 }
                 ::core::result::Result::Ok(Self::#var_ident(bits))
             }});
@@ -219,11 +264,17 @@ pub fn emit(prop: &BitProperty, settings: &Settings) -> TokenStream {
                     }
                 }
 
-                fn from_string(s: &str, _: &A) -> ::core::result::Result<Self, ()> {
+                fn from_string(s: &str, _: &A) -> ::core::result::Result<Self, ::bittwiddler_core::prelude::StringConvError> {
+                    let err = || {
+                        ::bittwiddler_core::prelude::StringConvError::expected_one_of(
+                            ::bittwiddler_core::prelude::StringConvError::new(s),
+                            &[#(#var_expected_strs),*]
+                        )
+                    };
                     let (s, _bits_s) = ::core::option::Option::unwrap_or(::core::primitive::str::split_once(s, '('), (s, ""));
                     match s {
                         #(#var_decode_str_matches)*
-                        _ => ::core::result::Result::Err(()),
+                        _ => ::core::result::Result::Err(err()),
                     }
                 }
             }
@@ -232,6 +283,53 @@ pub fn emit(prop: &BitProperty, settings: &Settings) -> TokenStream {
         quote! {}
     };
 
+    let bool_array = quote! {[::core::primitive::bool; #num_bits]};
+    let try_from_bits_ret = quote! {
+        ::core::result::Result<Self, ::bittwiddler_core::prelude::UnrecognizedBitsError<#bool_array>>
+    };
+    let decode_body = quote! {
+        match bits {
+            #(#var_decode_matches),*
+        }
+    };
+    let (from_bits_body, maybe_checked_from_bits, maybe_try_from) = if !settings.emit_try_from_bits
+    {
+        (decode_body, quote! {}, quote! {})
+    } else {
+        let maybe_try_from = quote! {
+            impl ::core::convert::TryFrom<#bool_array> for #prop_name_ident {
+                type Error = ::bittwiddler_core::prelude::UnrecognizedBitsError<#bool_array>;
+
+                fn try_from(bits: #bool_array) -> #try_from_bits_ret {
+                    <Self as ::bittwiddler_core::prelude::PropertyLeaf<#bool_array>>::checked_from_bits(&bits)
+                }
+            }
+        };
+        if prop.catchall_variant.is_some() {
+            // every pattern is valid, so the default checked_from_bits is correct
+            (decode_body, quote! {}, maybe_try_from)
+        } else {
+            (
+                quote! {
+                    match <Self as ::bittwiddler_core::prelude::PropertyLeaf<#bool_array>>::checked_from_bits(bits) {
+                        ::core::result::Result::Ok(x) => x,
+                        ::core::result::Result::Err(e) => ::core::panic!("{}", e),
+                    }
+                },
+                quote! {
+                    fn checked_from_bits(bits: &#bool_array) -> #try_from_bits_ret {
+                        #[allow(unreachable_patterns)]
+                        match bits {
+                            #(#var_try_decode_matches,)*
+                            _ => ::core::result::Result::Err(::bittwiddler_core::prelude::UnrecognizedBitsError { bits: *bits }),
+                        }
+                    }
+                },
+                maybe_try_from,
+            )
+        }
+    };
+
     quote! {
         #documentation
         #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -241,9 +339,7 @@ pub fn emit(prop: &BitProperty, settings: &Settings) -> TokenStream {
 
         impl ::bittwiddler_core::prelude::PropertyLeaf<[::core::primitive::bool; #num_bits]> for #prop_name_ident {
             fn from_bits(bits: &[::core::primitive::bool; #num_bits]) -> Self {
-                match bits {
-                    #(#var_decode_matches),*
-                }
+                #from_bits_body
             }
 
             fn to_bits(&self) -> [::core::primitive::bool; #num_bits] {
@@ -251,8 +347,17 @@ pub fn emit(prop: &BitProperty, settings: &Settings) -> TokenStream {
                     #(#var_encode_matches),*
                 }
             }
+
+            #maybe_checked_from_bits
+
+            const SCHEMA: ::bittwiddler_core::prelude::LeafSchema = ::bittwiddler_core::prelude::LeafSchema {
+                doc: #prop_doc,
+                kind: ::bittwiddler_core::prelude::LeafKind::Enum(&[#(#var_schemas),*]),
+            };
         }
 
+        #maybe_try_from
+
         #maybe_string_formatter
 
         #maybe_impl_default
@@ -18,6 +18,9 @@ pub enum EmitError {
         missing_bit_idx: usize,
         code_ident: String,
     },
+    InvalidPlane {
+        plane: String,
+    },
 }
 impl Display for EmitError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -34,14 +37,46 @@ impl Display for EmitError {
                 "missing bit {} for instance {} of {}",
                 missing_bit_idx, instance_idx, code_ident
             ),
+            EmitError::InvalidPlane { plane } => {
+                write!(f, "plane \"{}\" is not a valid expression", plane)
+            }
         }
     }
 }
 impl Error for EmitError {}
 
+/// Turns a constant name such as `PROPERTY_ONE` into a type name such as `PropertyOne`
+fn const_to_camel_case(s: &str) -> String {
+    let mut ret = String::new();
+    for word in s.split('_') {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            ret.extend(first.to_uppercase());
+            ret.extend(chars.flat_map(|c| c.to_lowercase()));
+        }
+    }
+    ret
+}
+
 pub fn emit(tile: &Tile) -> Result<TokenStream, EmitError> {
     let tile_name_id = Ident::new(&tile.name, Span::call_site());
 
+    // the plane is emitted as-is, so paths must be relative to inside the tile's module
+    let plane = match &tile.plane {
+        Some(plane) => {
+            Some(
+                syn::parse_str::<syn::Expr>(plane).map_err(|_| EmitError::InvalidPlane {
+                    plane: plane.clone(),
+                })?,
+            )
+        }
+        None => None,
+    };
+    let plane_const_val = match &plane {
+        Some(plane) => quote! {#plane},
+        None => quote! {0},
+    };
+
     // loop over everything looking for max numbers
     #[derive(Debug)]
     struct PropertySizeInfo {
@@ -76,11 +111,12 @@ pub fn emit(tile: &Tile) -> Result<TokenStream, EmitError> {
     }
 
     // loop over everything again, writing down the bit positions
+    // (a bit can appear in more than one cell if it is replicated, in which case all copies are kept)
     let mut property_coords = HashMap::new();
     for (prop_code_ident, prop_sz_info) in property_info.into_iter() {
         property_coords.insert(
             prop_code_ident,
-            vec![vec![None; prop_sz_info.num_bits]; prop_sz_info.num_instances],
+            vec![vec![Vec::new(); prop_sz_info.num_bits]; prop_sz_info.num_instances],
         );
     }
     for row in 0..tile.grid.len() {
@@ -88,51 +124,98 @@ pub fn emit(tile: &Tile) -> Result<TokenStream, EmitError> {
             if let Some(bit) = &tile.grid[row][col] {
                 let prop_code_ident = tile.spreadsheet_sym_map.get(&bit.spreadsheet_sym).unwrap();
                 property_coords.entry(prop_code_ident).and_modify(|x| {
-                    x[bit.instance_address.unwrap_or(0)][bit.bit_idx] = Some((row, col));
+                    x[bit.instance_address.unwrap_or(0)][bit.bit_idx].push((row, col));
+                });
+            }
+        }
+    }
+
+    // build the reverse lookup table (in row-major order, so that output is deterministic)
+    let mut prop_ids = property_coords
+        .keys()
+        .map(|prop_code_ident| {
+            (
+                (*prop_code_ident).clone(),
+                Ident::new(&const_to_camel_case(prop_code_ident), Span::call_site()),
+            )
+        })
+        .collect::<Vec<_>>();
+    prop_ids.sort();
+    let prop_id_map = prop_ids.iter().cloned().collect::<HashMap<_, _>>();
+    let prop_id_variants = prop_ids.iter().map(|(_, id)| id);
+    let mut owner_arms = Vec::new();
+    for row in 0..tile.grid.len() {
+        for col in 0..tile.grid[row].len() {
+            if let Some(bit) = &tile.grid[row][col] {
+                let prop_code_ident = tile.spreadsheet_sym_map.get(&bit.spreadsheet_sym).unwrap();
+                let prop_id = &prop_id_map[prop_code_ident];
+                let instance_idx = bit.instance_address.unwrap_or(0);
+                let bit_idx = bit.bit_idx;
+                owner_arms.push(quote! {
+                    (#col, #row) => ::core::option::Option::Some((PropertyId::#prop_id, #instance_idx, #bit_idx)),
                 });
             }
         }
     }
 
     // emit the code
+    let coord_code = |(row, col): (usize, usize)| {
+        if plane.is_some() {
+            quote! {
+                ::bittwiddler_core::prelude::Coordinate::new_in_plane(#col, #row, PLANE)
+            }
+        } else {
+            quote! {
+                ::bittwiddler_core::prelude::Coordinate::new(#col, #row)
+            }
+        }
+    };
     let output_coords_code = property_coords
         .into_iter()
         .map(|(prop_code_ident, coords)| {
             let const_ident = Ident::new(&prop_code_ident, Span::call_site());
+            let copies_const_ident = Ident::new(&format!("{}_COPIES", prop_code_ident), Span::call_site());
 
-            let coords_for_each_instance = coords.iter().enumerate().map(|(instance_idx, inst_coords)| {
-                let coords = inst_coords.into_iter().enumerate().map(|(bit_idx, c)| {
-                    if c.is_none() {
+            let mut coords_for_each_instance = Vec::new();
+            let mut copies_for_each_instance = Vec::new();
+            for (instance_idx, inst_coords) in coords.iter().enumerate() {
+                let mut first_coords = Vec::new();
+                let mut all_coords = Vec::new();
+                for (bit_idx, copies) in inst_coords.iter().enumerate() {
+                    if copies.is_empty() {
                         return Err(EmitError::MissingBit { instance_idx, missing_bit_idx: bit_idx, code_ident: prop_code_ident.clone() })
                     }
-                    let (row, col) = c.unwrap();
-                    Ok(quote! {
-                        ::bittwiddler_core::prelude::Coordinate::new(#col, #row)
-                    })
-                }).collect::<Result<Vec<_>, _>>();
-
-                if let Err(e) = coords {
-                    return Err(e);
+                    first_coords.push(coord_code(copies[0]));
+                    let copies = copies.iter().map(|c| coord_code(*c));
+                    all_coords.push(quote! {&[#(#copies),*]});
                 }
-                let coords = coords.unwrap();
-
-                Ok(quote!{&[#(#coords),*]})
-            }).collect::<Result<Vec<_>, _>>();
-
-            if let Err(e) = coords_for_each_instance {
-                return Err(e);
+                coords_for_each_instance.push(quote!{&[#(#first_coords),*]});
+                copies_for_each_instance.push(quote!{&[#(#all_coords),*]});
             }
-            let coords_for_each_instance = coords_for_each_instance.unwrap();
+            let is_replicated = coords.iter().flatten().any(|copies| copies.len() > 1);
 
-            if coords.len() == 1 {
-                Ok(quote! {
+            let mut ret = if coords.len() == 1 {
+                quote! {
                     pub const #const_ident: &'static [::bittwiddler_core::prelude::Coordinate] = #(#coords_for_each_instance)*;
-                })
+                }
             } else {
-                Ok(quote! {
+                quote! {
                     pub const #const_ident: &'static [&'static [::bittwiddler_core::prelude::Coordinate]] = &[#(#coords_for_each_instance),*];
-                })
+                }
+            };
+            // replicated bits additionally get every copy of every bit, with the first copy first
+            if is_replicated {
+                if coords.len() == 1 {
+                    ret.extend(quote! {
+                        pub const #copies_const_ident: &'static [&'static [::bittwiddler_core::prelude::Coordinate]] = #(#copies_for_each_instance)*;
+                    });
+                } else {
+                    ret.extend(quote! {
+                        pub const #copies_const_ident: &'static [&'static [&'static [::bittwiddler_core::prelude::Coordinate]]] = &[#(#copies_for_each_instance),*];
+                    });
+                }
             }
+            Ok(ret)
         }).collect::<Result<Vec<_>, _>>()?;
 
     let width = tile.grid[0].len();
@@ -142,8 +225,155 @@ pub fn emit(tile: &Tile) -> Result<TokenStream, EmitError> {
         pub mod #tile_name_id {
             pub const W: ::core::primitive::usize = #width;
             pub const H: ::core::primitive::usize = #height;
+            /// Plane which every coordinate in this tile is in
+            ///
+            /// The coordinates below are tile-local and already in this plane.
+            /// Adding the tile's origin on the right-hand side (`PROPERTY[i] + origin`) keeps it.
+            pub const PLANE: ::core::primitive::usize = #plane_const_val;
 
             #(#output_coords_code)*
+
+            /// Identifies one of the properties in this tile
+            #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+            pub enum PropertyId {
+                #(#prop_id_variants),*
+            }
+
+            /// Returns which property, instance, and bit index is stored at the tile-local coordinate `c`
+            pub fn owner(
+                c: ::bittwiddler_core::prelude::Coordinate,
+            ) -> ::core::option::Option<(PropertyId, ::core::primitive::usize, ::core::primitive::usize)> {
+                if c.plane != PLANE {
+                    return ::core::option::Option::None;
+                }
+                match (c.x, c.y) {
+                    #(#owner_arms)*
+                    _ => ::core::option::Option::None,
+                }
+            }
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spreadsheet::parse_spreadsheet::parse;
+    use std::path::PathBuf;
+    use syn::{parse_quote, Arm, Expr, Item, ItemConst, ItemMod, Stmt};
+
+    fn emit_test_tile(file: &str) -> Vec<Item> {
+        let mut p = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        p.push("tests");
+        p.push(file);
+        let tiles = parse(p).unwrap();
+        let m: ItemMod = syn::parse2(emit(&tiles[0]).unwrap()).unwrap();
+        m.content.unwrap().1
+    }
+
+    fn find_const<'a>(items: &'a [Item], name: &str) -> Option<&'a ItemConst> {
+        items.iter().find_map(|item| match item {
+            Item::Const(c) if c.ident == name => Some(c),
+            _ => None,
+        })
+    }
+
+    /// Elements of a `&[...]` expression
+    fn slice_elems(e: &Expr) -> Vec<&Expr> {
+        let Expr::Reference(r) = e else {
+            panic!("not a reference: {:?}", e);
+        };
+        let Expr::Array(a) = &*r.expr else {
+            panic!("not an array: {:?}", r.expr);
+        };
+        a.elems.iter().collect()
+    }
+
+    /// Everything which is not a `&[...]` inside of nested `&[...]` expressions
+    fn leaf_elems(e: &Expr) -> Vec<&Expr> {
+        match e {
+            Expr::Reference(_) => slice_elems(e).into_iter().flat_map(leaf_elems).collect(),
+            _ => vec![e],
+        }
+    }
+
+    fn owner_arms(items: &[Item]) -> &[Arm] {
+        let owner = items
+            .iter()
+            .find_map(|item| match item {
+                Item::Fn(f) if f.sig.ident == "owner" => Some(f),
+                _ => None,
+            })
+            .unwrap();
+        let Some(Stmt::Expr(Expr::Match(m), None)) = owner.block.stmts.last() else {
+            panic!("owner does not end in a match");
+        };
+        &m.arms
+    }
+
+    #[test]
+    fn test_emit_tile_plane() {
+        let items = emit_test_tile("testtile-plane.ods");
+
+        let plane: Expr = parse_quote!(super::UFM);
+        assert_eq!(*find_const(&items, "PLANE").unwrap().expr, plane);
+
+        let coords = items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Const(c) if c.ident != "PLANE" && c.ident != "W" && c.ident != "H" => {
+                    Some(leaf_elems(&c.expr))
+                }
+                _ => None,
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+        assert!(!coords.is_empty());
+        for c in &coords {
+            let Expr::Call(call) = c else {
+                panic!("not a call: {:?}", c);
+            };
+            let func: Expr = parse_quote!(::bittwiddler_core::prelude::Coordinate::new_in_plane);
+            assert_eq!(*call.func, func);
+            let plane: Expr = parse_quote!(PLANE);
+            assert_eq!(call.args.last(), Some(&plane));
+        }
+        let origin: Expr = parse_quote!(::bittwiddler_core::prelude::Coordinate::new_in_plane(
+            0usize, 0usize, PLANE
+        ));
+        assert!(coords.contains(&&origin));
+    }
+
+    #[test]
+    fn test_emit_tile_dup_bits() {
+        let items = emit_test_tile("testtile-dupbits.ods");
+
+        let c_0_0: Expr =
+            parse_quote!(::bittwiddler_core::prelude::Coordinate::new(0usize, 0usize));
+        let c_2_3: Expr =
+            parse_quote!(::bittwiddler_core::prelude::Coordinate::new(2usize, 3usize));
+
+        // the first copy is still the one in the plain constant
+        let p1 = find_const(&items, "PROPERTY_ONE").unwrap();
+        assert_eq!(slice_elems(&p1.expr)[0], &c_0_0);
+        let p1_copies = find_const(&items, "PROPERTY_ONE_COPIES").unwrap();
+        assert_eq!(
+            slice_elems(slice_elems(&p1_copies.expr)[0]),
+            [&c_0_0, &c_2_3]
+        );
+        assert!(find_const(&items, "PROPERTY_THREE_COPIES").is_some());
+        assert!(find_const(&items, "PROPERTY_TWO_COPIES").is_none());
+
+        let pat: syn::Pat = parse_quote!((2usize, 3usize));
+        let body: Expr = parse_quote!(::core::option::Option::Some((
+            PropertyId::PropertyOne,
+            0usize,
+            0usize
+        )));
+        let arm = owner_arms(&items)
+            .iter()
+            .find(|arm| arm.pat == pat)
+            .unwrap();
+        assert_eq!(*arm.body, body);
+    }
+}
@@ -17,6 +17,8 @@ pub struct Tile {
     pub name: String,
     pub grid: Vec<Vec<Option<TileBit>>>,
     pub spreadsheet_sym_map: HashMap<String, String>,
+    /// Rust expression for the plane this tile is in, from a `@plane` row in the symbol map
+    pub plane: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -195,10 +197,22 @@ pub fn parse<P: AsRef<Path>>(p: P) -> Result<Vec<Tile>, TopError> {
         }
 
         let mut spreadsheet_sym_map = HashMap::new();
+        let mut plane = None;
         for row in start_row_of_map..=end_row {
             let spreadsheet_sym = cell_to_string(data.get_value((row, 0)));
             let code_name = cell_to_string(data.get_value((row, 1)));
             if let (Some(spreadsheet_sym), Some(code_name)) = (spreadsheet_sym, code_name) {
+                if spreadsheet_sym == "@plane" {
+                    if plane.replace(code_name).is_some() {
+                        return Err(ParseError::DuplicateSymMapSym {
+                            row,
+                            ident: spreadsheet_sym,
+                        }
+                        .into());
+                    }
+                    continue;
+                }
+
                 let ret = spreadsheet_sym_map.insert(spreadsheet_sym.clone(), code_name.clone());
 
                 if ret.is_some() {
@@ -217,6 +231,7 @@ pub fn parse<P: AsRef<Path>>(p: P) -> Result<Vec<Tile>, TopError> {
             name: sheet,
             grid: tile_data,
             spreadsheet_sym_map,
+            plane,
         })
     }
 
@@ -255,6 +270,8 @@ mod tests {
         let p5_map = tile.spreadsheet_sym_map.get("P5").unwrap();
         assert_eq!(p5_map, "PROPERTY_FIVE");
 
+        assert_eq!(tile.plane, None);
+
         let grid = &tile.grid;
         assert_eq!(
             grid,
@@ -343,6 +360,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_tile_plane() {
+        let mut p = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        p.push("tests/testtile-plane.ods");
+        let result = parse(p).unwrap();
+
+        let tile = &result[0];
+        assert_eq!(tile.spreadsheet_sym_map.len(), 5);
+        assert_eq!(tile.plane.as_deref(), Some("super::UFM"));
+    }
+
+    #[test]
+    fn test_parse_tile_dup_bits() {
+        let mut p = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        p.push("tests/testtile-dupbits.ods");
+        let result = parse(p).unwrap();
+
+        let tile = &result[0];
+        assert_eq!(
+            tile.grid[3][2],
+            Some(TileBit {
+                spreadsheet_sym: "P1".into(),
+                instance_address: None,
+                bit_idx: 0,
+            })
+        );
+    }
+
     #[test]
     fn test_parse_tile_bad_cell() {
         let mut p = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -0,0 +1,86 @@
+use bittwiddler_core::prelude::*;
+use bittwiddler_example_test::*;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+/// A property which is an entire row of 64 bits, with or without describing itself as a run
+struct WideAccessor {
+    row: usize,
+    use_runs: bool,
+}
+impl PropertyAccessor for WideAccessor {
+    type BoolArray = [bool; 64];
+    type Output = u64;
+
+    fn get_bit_pos(&self, biti: usize) -> (Coordinate, bool) {
+        (Coordinate::new(3 + biti, self.row), false)
+    }
+
+    fn num_bit_runs(&self) -> usize {
+        if self.use_runs {
+            1
+        } else {
+            0
+        }
+    }
+    fn get_bit_run(&self, _runi: usize) -> BitRun {
+        BitRun {
+            start: Coordinate::new(3, self.row),
+            dir: RunDirection::Right,
+            len: 64,
+            invert: false,
+        }
+    }
+}
+
+fn dump_all_tiles(bitstream: &(impl BitArray + ?Sized)) {
+    for y in 0..4 {
+        for x in 0..4 {
+            let tile = TestBitstream::tile(x, y);
+            black_box(tile.property_one().get(bitstream));
+            for n in 0..4 {
+                black_box(tile.property_two(n).get(bitstream));
+            }
+            black_box(tile.property_three().get(bitstream));
+            black_box(tile.property_four().get(bitstream));
+            black_box(tile.property_five().get(bitstream));
+        }
+    }
+}
+
+fn example_accessors(c: &mut Criterion) {
+    let bitstream = TestBitstream { bits: [true; 256] };
+    c.bench_function("example dump TestBitstream", |b| {
+        b.iter(|| dump_all_tiles(black_box(&bitstream)))
+    });
+
+    let mut plane = BitPlane::new(16, 16);
+    plane.fill(true);
+    c.bench_function("example dump BitPlane", |b| {
+        b.iter(|| dump_all_tiles(black_box(&plane)))
+    });
+}
+
+fn wide_accessors(c: &mut Criterion) {
+    let mut plane = BitPlane::new(128, 128);
+    for use_runs in [false, true] {
+        let name = if use_runs { "runs" } else { "per-bit" };
+
+        c.bench_function(&format!("wide set {name}"), |b| {
+            b.iter(|| {
+                for row in 0..128 {
+                    WideAccessor { row, use_runs }.set(&mut plane, black_box(row as u64));
+                }
+            })
+        });
+        c.bench_function(&format!("wide get {name}"), |b| {
+            b.iter(|| {
+                for row in 0..128 {
+                    black_box(WideAccessor { row, use_runs }.get(&plane));
+                }
+            })
+        });
+    }
+}
+
+criterion_group!(benches, example_accessors, wide_accessors);
+criterion_main!(benches);
@@ -20,6 +20,23 @@ fn main() {
         write!(f, "{}", result_ts).unwrap();
         println!("cargo:rerun-if-changed=bitproperty-out.rs");
     }
+    {
+        let mut p = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        p.push("../bittwiddler-dsl/tests/bitproperty-nocatchall.txt");
+        let f = File::open(p).unwrap();
+        let result = parse_bit_property::parse(f).unwrap();
+        let mut settings = emit_bit_property::Settings::default();
+        settings.enable_no_std = true;
+        settings.alloc_feature_gate = Some("alloc".to_string());
+        settings.emit_try_from_bits = true;
+        let result_ts = emit_bit_property::emit(&result, &settings);
+
+        let mut p = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+        p.push("bitproperty-nocatchall-out.rs");
+        let mut f = File::create(p).unwrap();
+        write!(f, "{}", result_ts).unwrap();
+        println!("cargo:rerun-if-changed=bitproperty-nocatchall-out.rs");
+    }
     {
         let mut p = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         p.push("../bittwiddler-dsl/tests/testtile.ods");
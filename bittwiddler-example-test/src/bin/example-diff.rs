@@ -0,0 +1,25 @@
+use std::{env, fs, process::ExitCode};
+
+use bittwiddler_example_test::TestBitstream;
+
+fn main() -> Result<ExitCode, std::io::Error> {
+    let args = env::args_os().collect::<Vec<_>>();
+    if args.len() < 3 {
+        println!("Usage: {} old.txt new.txt", args[0].to_string_lossy());
+        return Ok(ExitCode::FAILURE);
+    }
+
+    let mut bitstreams = Vec::new();
+    for filename in &args[1..3] {
+        let Ok(bitstream) = fs::read_to_string(filename)?.parse::<TestBitstream>() else {
+            println!("{} is not a valid bitstream", filename.to_string_lossy());
+            return Ok(ExitCode::FAILURE);
+        };
+        bitstreams.push(bitstream);
+    }
+
+    let diff = bittwiddler_tools::diff::diff(&bitstreams[0], &bitstreams[1], &bitstreams[0]);
+    print!("{}", diff);
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,70 @@
+use std::{env, fs, io::BufWriter, process::ExitCode};
+
+use bittwiddler_example_test::{test_tile, TestBitstream};
+use bittwiddler_tools::lookup::BitOwnerIndex;
+use bittwiddler_tools::render::{render, PropertyColors, RenderSettings};
+
+fn main() -> Result<ExitCode, std::io::Error> {
+    let args = env::args().collect::<Vec<_>>();
+    let mut grid = false;
+    let mut colors = false;
+    let mut reference = None;
+    let mut files = Vec::new();
+    let mut args_iter = args[1..].iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--grid" => grid = true,
+            "--colors" => colors = true,
+            "--reference" => reference = args_iter.next(),
+            _ => files.push(arg),
+        }
+    }
+    if files.len() != 2 {
+        println!(
+            "Usage: {} [--grid] [--colors] [--reference ref.txt] bitstream.txt out.{{png,ppm}}",
+            args[0]
+        );
+        return Ok(ExitCode::FAILURE);
+    }
+
+    let read = |filename: &str| {
+        let bitstream = fs::read_to_string(filename)?.parse::<TestBitstream>();
+        if bitstream.is_err() {
+            println!("{} is not a valid bitstream", filename);
+        }
+        Ok::<_, std::io::Error>(bitstream.ok())
+    };
+    let Some(bitstream) = read(files[0])? else {
+        return Ok(ExitCode::FAILURE);
+    };
+    let reference = match reference {
+        Some(filename) => match read(filename)? {
+            Some(x) => Some(x),
+            None => return Ok(ExitCode::FAILURE),
+        },
+        None => None,
+    };
+
+    let index = BitOwnerIndex::new(&bitstream);
+    let settings = RenderSettings {
+        scale: 16,
+        tile_size: grid.then_some((test_tile::W, test_tile::H)),
+        colors: if colors {
+            PropertyColors::Hierarchy(&index)
+        } else {
+            PropertyColors::None
+        },
+        reference: reference.as_ref().map(|x| x as _),
+        ..Default::default()
+    };
+    let img = render(&bitstream, &settings);
+
+    let out = BufWriter::new(fs::File::create(files[1])?);
+    if files[1].ends_with(".ppm") {
+        img.write_ppm(out)?;
+    } else {
+        img.write_png(out)?;
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
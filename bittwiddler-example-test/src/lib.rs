@@ -5,10 +5,9 @@ use alloc::borrow::Cow;
 
 use bittwiddler_core::prelude::*;
 use bittwiddler_macros::*;
-#[cfg(feature = "alloc")]
-use itertools::Itertools;
 
 include!(concat!(env!("OUT_DIR"), "/bitproperty-out.rs"));
+include!(concat!(env!("OUT_DIR"), "/bitproperty-nocatchall-out.rs"));
 include!(concat!(env!("OUT_DIR"), "/tiles-out.rs"));
 
 pub struct TestBitstream {
@@ -24,21 +23,31 @@ impl BitArray for TestBitstream {
         self.bits[c.y * 16 + c.x] = val;
     }
 }
+impl BoundedBitArray for TestBitstream {
+    fn width(&self) -> usize {
+        16
+    }
+    fn height(&self) -> usize {
+        16
+    }
+}
 
 impl ToString for TestBitstream {
     fn to_string(&self) -> String {
-        let mut ret = String::with_capacity(17 * 16);
-        for y in 0..16 {
-            for x in 0..16 {
-                if self.get((x, y).into()) {
-                    ret.push('1');
-                } else {
-                    ret.push('0');
-                }
-            }
-            ret.push('\n');
-        }
-        ret
+        let mut ret = Vec::with_capacity(17 * 16);
+        bittwiddler_textfile::write_raw(&mut ret, self, 16, 16, None).unwrap();
+        String::from_utf8(ret).unwrap()
+    }
+}
+
+/// Parses the format written by [ToString] (16 lines of 16 `0`/`1` characters)
+impl core::str::FromStr for TestBitstream {
+    type Err = bittwiddler_textfile::TopError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bitstream = TestBitstream { bits: [false; 256] };
+        bittwiddler_textfile::read_raw(s.as_bytes(), &mut bitstream, 16, 16)?;
+        Ok(bitstream)
     }
 }
 
@@ -77,17 +86,10 @@ impl HumanLevelThatHasState for TestBitstream {
     fn _human_dump_my_state(&self, _dump: &mut dyn HumanSinkForStatePieces) {}
 }
 
-#[cfg(feature = "alloc")]
-impl TestBitstreamAutomagicRequiredFunctions for TestBitstream {
-    fn _automagic_construct_all_tile(&self) -> impl Iterator<Item = Tile> {
-        (0..4)
-            .cartesian_product(0..4)
-            .map(|(y, x)| Self::tile(x, y))
-    }
-}
-
 #[bittwiddler_properties(alloc_feature_gate = "alloc")]
 impl TestBitstream {
+    /// One of the 4x4 tiles
+    #[bittwiddler::range(y = 0..4, x = 0..4)]
     pub fn tile(x: u8, y: u8) -> Tile {
         Tile { x, y }
     }
@@ -103,15 +105,6 @@ pub struct Tile {
     y: u8,
 }
 
-#[cfg(feature = "alloc")]
-impl TileAutomagicRequiredFunctions for Tile {
-    fn _automagic_construct_all_property_two(
-        &self,
-    ) -> impl Iterator<Item = TilePropertyTwoAccessor> {
-        (0..4).map(|n| self.property_two(n))
-    }
-}
-
 #[bittwiddler_hierarchy_level(alloc_feature_gate = "alloc")]
 pub struct DummySublevel;
 #[bittwiddler_properties(alloc_feature_gate = "alloc")]
@@ -143,7 +136,7 @@ impl Tile {
         TilePropertyOneAccessor { tile: self.clone() }
     }
     #[bittwiddler::property]
-    pub fn property_two(&self, n: u8) -> TilePropertyTwoAccessor {
+    pub fn property_two(&self, #[bittwiddler::range(0..4)] n: u8) -> TilePropertyTwoAccessor {
         TilePropertyTwoAccessor {
             tile: self.clone(),
             n,
@@ -161,6 +154,11 @@ impl Tile {
     pub fn property_five(&self) -> TilePropertyFiveAccessor {
         TilePropertyFiveAccessor { tile: self.clone() }
     }
+    #[bittwiddler::property]
+    #[bittwiddler::var_width]
+    pub fn property_six(&self) -> TilePropertySixAccessor {
+        TilePropertySixAccessor { tile: self.clone() }
+    }
 }
 
 #[bittwiddler_hierarchy_level(alloc_feature_gate = "alloc")]
@@ -181,6 +179,18 @@ impl PropertyAccessor for TilePropertyOneAccessor {
             false,
         )
     }
+    fn num_bit_runs(&self) -> usize {
+        1
+    }
+    fn get_bit_run(&self, _runi: usize) -> BitRun {
+        // all four bits are next to each other in the spreadsheet
+        BitRun {
+            start: self.get_bit_pos(0).0,
+            dir: RunDirection::Right,
+            len: 4,
+            invert: false,
+        }
+    }
 }
 impl PropertyAccessorWithDefault for TilePropertyOneAccessor {}
 #[cfg(feature = "alloc")]
@@ -236,7 +246,10 @@ impl PropertyLeafWithStringConv<[bool; 1], TilePropertyThreeAccessor> for Custom
         }
     }
 
-    fn from_string(s: &str, _accessor: &TilePropertyThreeAccessor) -> Result<Self, ()> {
+    fn from_string(
+        s: &str,
+        _accessor: &TilePropertyThreeAccessor,
+    ) -> Result<Self, StringConvError> {
         if s == "nonono" {
             Ok(Self(false))
         } else {
@@ -254,7 +267,7 @@ impl PropertyLeafWithStringConv<[bool; 1], TilePropertyFourAccessor> for CustomB
         }
     }
 
-    fn from_string(s: &str, _accessor: &TilePropertyFourAccessor) -> Result<Self, ()> {
+    fn from_string(s: &str, _accessor: &TilePropertyFourAccessor) -> Result<Self, StringConvError> {
         if s == "lalala" {
             Ok(Self(false))
         } else {
@@ -331,9 +344,40 @@ impl PropertyAccessorWithDefault for TilePropertyFiveAccessor {}
 #[cfg(feature = "alloc")]
 impl PropertyAccessorWithStringConv for TilePropertyFiveAccessor {}
 
+/// Has (x + y) % 3 bits, so some tiles don't have this property at all
+#[bittwiddler_hierarchy_level(alloc_feature_gate = "alloc")]
+#[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+pub struct TilePropertySixAccessor {
+    tile: Tile,
+}
+#[cfg(feature = "alloc")]
+impl VarWidthPropertyAccessor for TilePropertySixAccessor {
+    type Output = u8;
+
+    fn nbits(&self) -> usize {
+        (self.tile.x as usize + self.tile.y as usize) % 3
+    }
+    fn get_bit_pos(&self, biti: usize) -> (Coordinate, bool) {
+        // bits go right-to-left starting from the bottom-right corner of the tile
+        (
+            Coordinate::new(3 - biti, 3)
+                + Coordinate::new(
+                    self.tile.x as usize * test_tile::W,
+                    self.tile.y as usize * test_tile::H,
+                ),
+            false,
+        )
+    }
+}
+#[cfg(feature = "alloc")]
+impl VarWidthPropertyAccessorWithDefault for TilePropertySixAccessor {}
+#[cfg(feature = "alloc")]
+impl VarWidthPropertyAccessorWithStringConv for TilePropertySixAccessor {}
+
 #[cfg(all(test, feature = "alloc"))]
 mod tests {
     use super::*;
+    use itertools::Itertools;
     const _1: bool = true;
     const _0: bool = false;
 
@@ -438,6 +482,386 @@ mod tests {
 
         let bit_str = bitstream.to_string();
         print!("{}", bit_str);
+        assert_eq!(
+            bit_str.parse::<TestBitstream>().unwrap().bits,
+            bitstream.bits
+        );
+    }
+
+    #[test]
+    fn test_var_width() {
+        let mut bitstream = TestBitstream { bits: [false; 256] };
+        let empty = TestBitstream::tile(0, 0).property_six();
+        let narrow = TestBitstream::tile(1, 0).property_six();
+        let wide = TestBitstream::tile(2, 0).property_six();
+        assert_eq!(empty.nbits(), 0);
+        assert_eq!(narrow.nbits(), 1);
+        assert_eq!(wide.nbits(), 2);
+        wide.set(&mut bitstream, 0b10);
+        assert!(BitArray::get(&bitstream, Coordinate::new(10, 3)));
+        assert_eq!(wide.get(&bitstream), 0b10);
+        assert!(narrow.set_from_string(&mut bitstream, "0x2").is_err());
+
+        let mut out = Vec::new();
+        bittwiddler_textfile::write(&mut out, &bitstream).unwrap();
+        let out = std::str::from_utf8(&out).unwrap();
+        assert!(!out.contains("tile[x=1, y=0].property_six"));
+        assert!(out.contains("tile[x=2, y=0].property_six = 0x2\n"));
+
+        let mut bitstream2 = TestBitstream { bits: [false; 256] };
+        bittwiddler_textfile::parse(out.as_bytes(), &mut bitstream2).unwrap();
+        assert_eq!(bitstream2.bits, bitstream.bits);
+    }
+
+    #[test]
+    fn test_try_get_decoded() {
+        struct Speed;
+        impl PropertyAccessor for Speed {
+            type BoolArray = [bool; 2];
+            type Output = Property2;
+
+            fn get_bit_pos(&self, biti: usize) -> (Coordinate, bool) {
+                (Coordinate::new(2 + biti, 3), false)
+            }
+        }
+
+        let mut bitstream = TestBitstream { bits: [false; 256] };
+        assert_eq!(Speed.try_get_decoded(&bitstream), Ok(Property2::Off));
+        Speed.set(&mut bitstream, Property2::Fast);
+        assert_eq!(Speed.try_get_decoded(&bitstream), Ok(Property2::Fast));
+
+        BitArray::set(&mut bitstream, Coordinate::new(3, 3), true);
+        let err = Speed.try_get_decoded(&bitstream).unwrap_err();
+        assert_eq!(err.bits, [true, true]);
+        assert_eq!(err.to_string(), "unrecognized bit pattern 11");
+        assert_eq!(Property2::try_from([false, true]), Ok(Property2::Slow));
+    }
+
+    #[test]
+    #[should_panic(expected = "unrecognized bit pattern 11")]
+    fn test_unrecognized_bits_panic() {
+        Property2::from_bits(&[true, true]);
+    }
+
+    #[test]
+    fn test_unrecognized_bits_dump() {
+        #[bittwiddler_hierarchy_level]
+        struct Speed;
+        impl PropertyAccessor for Speed {
+            type BoolArray = [bool; 2];
+            type Output = Property2;
+
+            fn get_bit_pos(&self, biti: usize) -> (Coordinate, bool) {
+                (Coordinate::new(2 + biti, 3), false)
+            }
+        }
+        impl PropertyAccessorWithDefault for Speed {}
+        impl PropertyAccessorWithStringConv for Speed {}
+        impl PropertyLeafWithDefault<[bool; 2], Speed> for Property2 {
+            fn is_default(&self, _accessor: &Speed) -> bool {
+                *self == Property2::Off
+            }
+        }
+
+        #[bittwiddler_hierarchy_level]
+        struct Chip;
+        #[bittwiddler_properties]
+        impl Chip {
+            #[bittwiddler::property]
+            pub fn speed(&self) -> Speed {
+                Speed
+            }
+        }
+
+        let mut bitstream = TestBitstream { bits: [false; 256] };
+        Speed.set(&mut bitstream, Property2::Fast);
+        let mut out = Vec::new();
+        bittwiddler_textfile::write_with_hierarchy(&mut out, &bitstream, &Chip).unwrap();
+        assert_eq!(out, b"speed = Fast\n");
+
+        // 11 isn't in bitproperty-nocatchall.txt
+        BitArray::set(&mut bitstream, Coordinate::new(3, 3), true);
+        let mut out = Vec::new();
+        bittwiddler_textfile::write_with_hierarchy(&mut out, &bitstream, &Chip).unwrap();
+        assert_eq!(out, b"speed = <unrecognized bit pattern 11>\n");
+    }
+
+    #[test]
+    fn test_derive_property_leaf() {
+        #[derive(PropertyLeaf, PartialEq, Eq, Debug)]
+        #[bittwiddler(bits = 8)]
+        struct Pll {
+            #[bittwiddler(bits = 3)]
+            div: u8,
+            #[bittwiddler(offset = 4, bits = 3)]
+            phase: OneHot<3>,
+            en: bool,
+        }
+
+        #[derive(PropertyLeaf, PartialEq, Eq, Debug, Default)]
+        #[bittwiddler(no_default)]
+        struct Pair(bool, #[bittwiddler(bits = 8)] Pll);
+
+        struct PllAccessor;
+        impl PropertyAccessor for PllAccessor {
+            type BoolArray = [bool; 9];
+            type Output = Pair;
+
+            fn get_bit_pos(&self, biti: usize) -> (Coordinate, bool) {
+                (Coordinate::new(biti, 0), false)
+            }
+        }
+        impl PropertyAccessorWithStringConv for PllAccessor {}
+
+        let LeafKind::Composite(pieces) = Pll::SCHEMA.kind else {
+            panic!("Pll should be a composite");
+        };
+        assert_eq!(
+            pieces
+                .iter()
+                .map(|x| (x.name, x.offset, x.nbits))
+                .collect::<Vec<_>>(),
+            [("div", 0, 3), ("phase", 4, 3), ("en", 7, 1)]
+        );
+        assert!(matches!((pieces[2].leaf)().kind, LeafKind::Enum(_)));
+
+        assert_eq!(
+            Pll::default(),
+            Pll {
+                div: 0,
+                phase: OneHot(None),
+                en: false
+            }
+        );
+
+        let val = Pair(
+            true,
+            Pll {
+                div: 3,
+                phase: OneHot(Some(1)),
+                en: true,
+            },
+        );
+        let mut bitstream = TestBitstream { bits: [false; 256] };
+        PllAccessor.set(&mut bitstream, val);
+        assert_eq!(bitstream.bits[..9], [_1, _1, _1, _0, _0, _0, _1, _0, _1]);
+        assert_eq!(
+            PllAccessor.get(&bitstream),
+            Pair(
+                true,
+                Pll {
+                    div: 3,
+                    phase: OneHot(Some(1)),
+                    en: true,
+                }
+            )
+        );
+
+        assert_eq!(
+            PllAccessor.get_as_string(&bitstream),
+            "{0=true, 1={div=0x3, phase=1, en=true}}"
+        );
+        PllAccessor
+            .set_from_string(
+                &mut bitstream,
+                "{1={en=false, div=0b101, phase=none}, 0=false}",
+            )
+            .unwrap();
+        assert_eq!(
+            PllAccessor.get(&bitstream),
+            Pair(
+                false,
+                Pll {
+                    div: 5,
+                    phase: OneHot(None),
+                    en: false,
+                }
+            )
+        );
+        // unknown, missing, and repeated fields
+        assert!(PllAccessor
+            .set_from_string(&mut bitstream, "{0=true, 1={div=0, phase=0, en=true, x=1}}")
+            .is_err());
+        assert_eq!(
+            PllAccessor
+                .set_from_string(&mut bitstream, "{0=true, 1={div=0, phase=0}}")
+                .unwrap_err()
+                .to_string(),
+            "invalid value \"{div=0, phase=0}\", expected {div=..., phase=..., en=...}"
+        );
+        assert!(PllAccessor
+            .set_from_string(
+                &mut bitstream,
+                "{0=true, 0=true, 1={div=0, phase=0, en=true}}"
+            )
+            .is_err());
+
+        // two phase bits set isn't a valid one-hot value
+        bitstream.bits[5] = true;
+        bitstream.bits[6] = true;
+        assert!(PllAccessor.try_get_decoded(&bitstream).is_err());
+    }
+
+    #[test]
+    fn test_derive_property_leaf_textfile() {
+        #[derive(PropertyLeaf, PartialEq, Eq, Debug)]
+        struct Pll {
+            #[bittwiddler(bits = 3)]
+            div: u8,
+            #[bittwiddler(bits = 2)]
+            phase: OneHot<2>,
+            en: bool,
+        }
+
+        struct PllBitstream {
+            bits: [bool; 6],
+        }
+        impl BitArray for PllBitstream {
+            fn get(&self, c: Coordinate) -> bool {
+                self.bits[c.x]
+            }
+
+            fn set(&mut self, c: Coordinate, val: bool) {
+                self.bits[c.x] = val;
+            }
+        }
+        impl HumanLevelThatHasState for PllBitstream {
+            fn _human_dump_my_state(&self, _dump: &mut dyn HumanSinkForStatePieces) {}
+        }
+        #[bittwiddler_properties]
+        impl PllBitstream {
+            #[bittwiddler::property]
+            pub fn pll() -> PllAccessor {
+                PllAccessor
+            }
+        }
+
+        #[bittwiddler_hierarchy_level]
+        struct PllAccessor;
+        impl PropertyAccessor for PllAccessor {
+            type BoolArray = [bool; 6];
+            type Output = Pll;
+
+            fn get_bit_pos(&self, biti: usize) -> (Coordinate, bool) {
+                (Coordinate::new(biti, 0), false)
+            }
+        }
+        impl PropertyAccessorWithDefault for PllAccessor {}
+        impl PropertyAccessorWithStringConv for PllAccessor {}
+
+        let mut bitstream = PllBitstream { bits: [false; 6] };
+        PllAccessor.set(
+            &mut bitstream,
+            Pll {
+                div: 5,
+                phase: OneHot(Some(1)),
+                en: true,
+            },
+        );
+        let mut out = Vec::new();
+        bittwiddler_textfile::write(&mut out, &bitstream).unwrap();
+        assert_eq!(out, b"pll = {div=0x5, phase=1, en=true}\n");
+
+        let mut bitstream2 = PllBitstream { bits: [false; 6] };
+        bittwiddler_textfile::parse(&out[..], &mut bitstream2).unwrap();
+        assert_eq!(bitstream2.bits, bitstream.bits);
+    }
+
+    #[test]
+    fn test_ranges_non_copy() {
+        #[bittwiddler_hierarchy_level]
+        struct Wire {
+            name: String,
+        }
+        impl PropertyAccessor for Wire {
+            type BoolArray = [bool; 1];
+            type Output = bool;
+
+            fn get_bit_pos(&self, _biti: usize) -> (Coordinate, bool) {
+                (Coordinate::new(0, 0), false)
+            }
+        }
+        impl PropertyAccessorWithDefault for Wire {}
+        impl PropertyAccessorWithStringConv for Wire {}
+
+        #[bittwiddler_hierarchy_level]
+        struct Chip;
+        #[bittwiddler_properties]
+        impl Chip {
+            #[bittwiddler::property]
+            #[bittwiddler::range(
+                a = ["n", "s"].map(String::from),
+                b = [a.clone() + "1"],
+                c = [b.clone() + "2", b.clone() + "3"]
+            )]
+            pub fn wire(&self, a: String, b: String, c: String) -> Wire {
+                Wire { name: a + &b + &c }
+            }
+        }
+
+        assert_eq!(Chip._human_construct_all_fields(0).count(), 4);
+        let wire = Chip::_human_schema().field("wire").unwrap();
+        assert_eq!(wire.args[0].ty, "String");
+        assert_eq!(
+            wire.args[0].values,
+            Some("[\"n\", \"s\"].map(String::from)")
+        );
+        assert_eq!(
+            (wire.arg_values.unwrap())().last(),
+            Some(vec!["s".into(), "s1".into(), "s13".into()])
+        );
+    }
+
+    #[test]
+    fn test_bit_runs() {
+        let mut plane = BitPlane::new(16, 16);
+        let mut bitstream = TestBitstream { bits: [false; 256] };
+        for (x, y) in (0..4).cartesian_product(0..4) {
+            let accessor = TestBitstream::tile(x, y).property_one();
+            accessor.set(&mut plane, Property1::ChoiceWithX([_0, _1, _0, _1]));
+            accessor.set(&mut bitstream, Property1::ChoiceWithX([_0, _1, _0, _1]));
+        }
+        for (x, y) in (0..4).cartesian_product(0..4) {
+            let accessor = TestBitstream::tile(x, y).property_one();
+            for biti in 0..4 {
+                let (c, _) = accessor.get_bit_pos(biti);
+                assert_eq!(plane.get(c), biti % 2 == 1);
+                assert_eq!(BitArray::get(&bitstream, c), biti % 2 == 1);
+            }
+            assert_eq!(
+                accessor.get(&plane),
+                Property1::ChoiceWithX([_0, _1, _0, _1])
+            );
+            assert_eq!(
+                accessor.get(&bitstream),
+                Property1::ChoiceWithX([_0, _1, _0, _1])
+            );
+        }
+    }
+
+    #[test]
+    fn test_out_of_bounds() {
+        let mut bitstream = TestBitstream { bits: [false; 256] };
+        let accessor = TestBitstream::tile(3, 3).property_one();
+        assert_eq!(
+            accessor.try_set(&mut bitstream, Property1::ChoiceThree),
+            Ok(())
+        );
+        assert_eq!(accessor.try_get(&bitstream), Ok(Property1::ChoiceThree));
+
+        let accessor = TestBitstream::tile(4, 0).property_one();
+        assert_eq!(
+            accessor.try_get(&bitstream),
+            Err(OutOfBoundsError {
+                coordinate: Coordinate::new(16, 0)
+            })
+        );
+        assert_eq!(
+            accessor.try_set(&mut bitstream, Property1::ChoiceThree),
+            Err(OutOfBoundsError {
+                coordinate: Coordinate::new(16, 0)
+            })
+        );
     }
 
     #[test]
@@ -450,6 +874,242 @@ mod tests {
         print!("{}", std::str::from_utf8(&out).unwrap());
     }
 
+    #[test]
+    fn test_unexplained_bits() {
+        let mut bitstream = TestBitstream { bits: [false; 256] };
+        bittwiddler_core::prelude::BitArray::set(&mut bitstream, Coordinate::new(1, 1), true);
+        bittwiddler_core::prelude::BitArray::set(&mut bitstream, Coordinate::new(2, 3), true);
+        bittwiddler_core::prelude::BitArray::set(&mut bitstream, Coordinate::new(15, 15), true);
+
+        let tracker = AccessTracker::new(bitstream);
+        let mut out = Vec::new();
+        bittwiddler_textfile::write_with_hierarchy(&mut out, &tracker, tracker.inner()).unwrap();
+
+        assert_eq!(tracker.untouched_set_bits(), [Coordinate::new(2, 3)]);
+        assert_eq!(tracker.counts(Coordinate::new(1, 1)).reads, 2);
+    }
+
+    #[test]
+    fn test_no_overlaps() {
+        let bitstream = TestBitstream { bits: [false; 256] };
+        let overlaps = bittwiddler_tools::overlap::find_overlaps(&bitstream);
+        for overlap in &overlaps {
+            println!("{}", overlap);
+        }
+        assert!(overlaps.is_empty());
+    }
+
+    #[test]
+    fn test_lookup() {
+        let bitstream = TestBitstream { bits: [false; 256] };
+        let index = bittwiddler_tools::lookup::BitOwnerIndex::new(&bitstream);
+        let owner = index.owner(Coordinate::new(6, 4)).unwrap();
+        assert_eq!(owner.path, "tile[x=1, y=1].property_one");
+        assert_eq!(owner.biti, 2);
+        assert!(!owner.invert);
+        assert_eq!(index.owners(Coordinate::new(3, 3)), []);
+
+        assert_eq!(
+            test_tile::owner(Coordinate::new(2, 0)),
+            Some((test_tile::PropertyId::PropertyOne, 0, 2))
+        );
+        assert_eq!(
+            test_tile::owner(Coordinate::new(3, 2)),
+            Some((test_tile::PropertyId::PropertyTwo, 3, 0))
+        );
+        assert_eq!(test_tile::owner(Coordinate::new(3, 3)), None);
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = TestBitstream { bits: [false; 256] };
+        let mut new = TestBitstream { bits: [false; 256] };
+        new.set_field(
+            &TestBitstream::tile(1, 0).property_one(),
+            Property1::ChoiceTwo,
+        );
+        bittwiddler_core::prelude::BitArray::set(&mut new, Coordinate::new(2, 3), true);
+
+        let diff = bittwiddler_tools::diff::diff(&old, &new, &old);
+        assert_eq!(
+            diff.to_string(),
+            "tile[x=1, y=0].property_one: ChoiceZero -> ChoiceTwo\n(2, 3): 0 -> 1\n"
+        );
+        assert!(bittwiddler_tools::diff::diff(&new, &new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_drc() {
+        use bittwiddler_textfile::accessor_path;
+        use bittwiddler_tools::drc::{field_rule, rule, Drc, RuleOutput, Severity};
+
+        let drc = Drc::new()
+            .with_rule(rule(
+                "two-needs-one",
+                |bitstream: &TestBitstream, out: &mut RuleOutput| {
+                    for y in 0..4 {
+                        for x in 0..4 {
+                            let tile = TestBitstream::tile(x, y);
+                            if bitstream.get_field(&tile.property_one()) != Property1::ChoiceZero {
+                                continue;
+                            }
+                            for n in 0..4 {
+                                let prop = tile.property_two(n);
+                                if bitstream.get_field(&prop) {
+                                    out.error(
+                                        accessor_path(&[("tile", &tile), ("property_two", &prop)]),
+                                        "cannot be set while property_one is ChoiceZero",
+                                    );
+                                }
+                            }
+                        }
+                    }
+                },
+            ))
+            .with_rule(field_rule(
+                "no-choice-three",
+                |path: &str,
+                 field: &dyn PropertyAccessorDyn,
+                 bitstream: &TestBitstream,
+                 out: &mut RuleOutput| {
+                    if field._human_string_get(bitstream) == "ChoiceThree" {
+                        out.warning(path, "ChoiceThree is deprecated");
+                    }
+                },
+            ));
+
+        let mut bitstream = TestBitstream { bits: [false; 256] };
+        assert!(drc.run(&bitstream).is_clean());
+
+        bitstream.set_field(
+            &TestBitstream::tile(1, 0).property_one(),
+            Property1::ChoiceThree,
+        );
+        bitstream.set_field(&TestBitstream::tile(1, 0).property_two(2), true);
+        let report = drc.check(&bitstream).unwrap();
+        assert_eq!(
+            report.to_string(),
+            "warning: tile[x=1, y=0].property_one: ChoiceThree is deprecated [no-choice-three]\n"
+        );
+
+        bitstream.set_field(&TestBitstream::tile(0, 2).property_two(1), true);
+        let report = drc.check(&bitstream).unwrap_err();
+        assert_eq!(report.errors().count(), 1);
+        let error = report.errors().next().unwrap();
+        assert_eq!(error.severity, Severity::Error);
+        assert_eq!(error.path, "tile[x=0, y=2].property_two[n=1]");
+        assert_eq!(error.rule, "two-needs-one");
+    }
+
+    #[test]
+    fn test_human_parse_errors() {
+        let mut bitstream = TestBitstream { bits: [false; 256] };
+        let parse = |s: &str, bitstream: &mut TestBitstream| {
+            bittwiddler_textfile::parse(s.as_bytes(), bitstream)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            parse("tile[0, 0].property_one = ChoiceFour", &mut bitstream),
+            "error on line 0: in 'property_one': invalid value \"ChoiceFour\", expected one of: \
+             ChoiceZero, ChoiceOne, ChoiceTwo, ChoiceThree, ChoiceWithX(4 bits), CatchallChoice(4 bits)"
+        );
+        assert_eq!(
+            parse("tile[0, 0].property_one = ChoiceWithX(01)", &mut bitstream),
+            "error on line 0: in 'property_one': invalid value \"ChoiceWithX(01)\", expected one of: \
+             ChoiceZero, ChoiceOne, ChoiceTwo, ChoiceThree, ChoiceWithX(4 bits), CatchallChoice(4 bits)"
+        );
+        assert_eq!(
+            parse("tile[x=0, y=a].property_one = ChoiceOne", &mut bitstream),
+            "error on line 0: in 'tile': invalid value \"a\" for argument y, expected u8"
+        );
+        assert_eq!(
+            parse("tile[0].property_one = ChoiceOne", &mut bitstream),
+            "error on line 0: in 'tile': invalid value \"0\", expected 2 arguments"
+        );
+
+        let err = StatePiece::from_human_string("300")
+            .map(|x: u8| x)
+            .unwrap_err();
+        assert_eq!(err.text, "300");
+        assert_eq!(err.expected, ["u8"]);
+        assert_eq!(err.arg, None);
+    }
+
+    #[test]
+    fn test_schema() {
+        let schema = TestBitstream::_human_schema();
+        assert_eq!(schema.name, "TestBitstream");
+        assert_eq!(
+            schema.sublevels.iter().map(|x| x.name).collect::<Vec<_>>(),
+            ["tile", "dummy_sublevel"]
+        );
+        assert!(schema.fields.is_empty());
+
+        let tile = schema.sublevel("tile").unwrap();
+        assert_eq!(tile.doc, Some("One of the 4x4 tiles"));
+        assert_eq!(
+            tile.args,
+            [
+                ArgSchema {
+                    name: "x",
+                    ty: "u8",
+                    values: Some("0..4"),
+                },
+                ArgSchema {
+                    name: "y",
+                    ty: "u8",
+                    values: Some("0..4"),
+                },
+            ]
+        );
+        assert!(!tile.conditional);
+        // y is outermost
+        let tile_args = (tile.arg_values.unwrap())().collect::<Vec<_>>();
+        assert_eq!(tile_args.len(), 16);
+        assert_eq!(tile_args[..2], [["0", "0"], ["1", "0"]]);
+        let dummy = schema.sublevel("dummy_sublevel").unwrap();
+        assert_eq!(
+            (dummy.arg_values.unwrap())().collect::<Vec<_>>(),
+            [Vec::<String>::new()]
+        );
+
+        let tile = (tile.level)();
+        assert_eq!(tile.name, "Tile");
+        assert!(tile.sublevels.is_empty());
+        let two = tile.field("property_two").unwrap();
+        assert_eq!(two.accessor, "TilePropertyTwoAccessor");
+        assert_eq!(two.args[0].name, "n");
+        assert_eq!(two.nbits, Some(1));
+        assert_eq!(tile.field("property_six").unwrap().nbits, None);
+
+        let one = tile.field("property_one").unwrap();
+        assert_eq!(one.nbits, Some(4));
+        let LeafKind::Enum(variants) = (one.leaf)().kind else {
+            panic!("property_one should be an enum");
+        };
+        assert_eq!(
+            variants.iter().map(|x| x.name).collect::<Vec<_>>(),
+            [
+                "ChoiceZero",
+                "ChoiceOne",
+                "ChoiceTwo",
+                "ChoiceThree",
+                "ChoiceWithX",
+                "CatchallChoice"
+            ]
+        );
+        assert_eq!(variants[4].pattern, Some("01xX"));
+        assert!(variants[4].keeps_bits);
+        assert_eq!(variants[5].pattern, None);
+
+        // the same schema can be reached dynamically
+        let bitstream = TestBitstream { bits: [false; 256] };
+        let level = bitstream._human_descend_sublevel(0, &["1", "2"]).unwrap();
+        assert!(core::ptr::eq(level._human_level_schema(), tile));
+    }
+
     #[test]
     fn test_human_write() {
         let mut bitstream = TestBitstream { bits: [false; 256] };
@@ -9,3 +9,8 @@ pub fn bittwiddler_hierarchy_level(attr: TokenStream, input: TokenStream) -> Tok
 pub fn bittwiddler_properties(attr: TokenStream, input: TokenStream) -> TokenStream {
     bittwiddler_dsl::macros::bittwiddler_properties(attr.into(), input.into()).into()
 }
+
+#[proc_macro_derive(PropertyLeaf, attributes(bittwiddler))]
+pub fn derive_property_leaf(input: TokenStream) -> TokenStream {
+    bittwiddler_dsl::macros::derive_property_leaf(input.into()).into()
+}
@@ -1,6 +1,10 @@
 //! This crate takes bitstreams and turns them to/from human-readable text files
 
 mod writer;
-pub use writer::write;
+pub use writer::{write, write_with_hierarchy};
 mod parser;
-pub use parser::parse;
+pub use parser::{parse, ParseError, TopError};
+mod raw;
+pub use raw::{read_pbm, read_raw, write_pbm, write_raw, PbmFormat};
+mod walk;
+pub use walk::{accessor_path, for_each_field, try_for_each_field};
@@ -10,8 +10,8 @@ use bittwiddler_core::prelude::*;
 
 #[derive(Debug)]
 pub struct ParseError {
-    line: usize,
-    message: String,
+    pub(crate) line: usize,
+    pub(crate) message: String,
 }
 impl Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -81,6 +81,23 @@ fn get_args(line_i: usize, level: &str) -> Result<(&str, Vec<&str>), ParseError>
     }
 }
 
+/// Split a line at the first `=` which isn't inside brackets or braces
+///
+/// Both the argument lists in the path (`tile[x=1, y=2]`) and composite values
+/// (`{div=0x3, en=true}`) can contain their own `=`.
+fn split_assignment(l: &str) -> Option<(&str, &str)> {
+    let mut depth = 0usize;
+    for (i, c) in l.char_indices() {
+        match c {
+            '[' | '{' => depth += 1,
+            ']' | '}' => depth = depth.saturating_sub(1),
+            '=' if depth == 0 => return Some((&l[..i], &l[i + 1..])),
+            _ => {}
+        }
+    }
+    None
+}
+
 pub fn parse<B: BitArray + HumanLevelDynamicAccessor, R: io::Read>(
     r: R,
     bitstream: &mut B,
@@ -95,7 +112,7 @@ pub fn parse<B: BitArray + HumanLevelDynamicAccessor, R: io::Read>(
             continue;
         }
 
-        if let Some((property, value)) = l.rsplit_once('=') {
+        if let Some((property, value)) = split_assignment(l) {
             let mut level: &dyn HumanLevelDynamicAccessor = bitstream;
             let mut boxes = Vec::new();
             let mut property = property.trim();
@@ -114,9 +131,9 @@ pub fn parse<B: BitArray + HumanLevelDynamicAccessor, R: io::Read>(
                     })?;
                 let x = level
                     ._human_descend_sublevel(idx, &args)
-                    .map_err(|_| ParseError {
+                    .map_err(|e| ParseError {
                         line: line_i,
-                        message: "arg was malformed".into(),
+                        message: format!("in \'{}\': {}", this_level_ident, e),
                     })?;
                 boxes.push(x);
                 level = &*boxes[boxes.len() - 1];
@@ -134,14 +151,14 @@ pub fn parse<B: BitArray + HumanLevelDynamicAccessor, R: io::Read>(
                 })?;
             let x = level
                 ._human_construct_field(idx, &args)
-                .map_err(|_| ParseError {
+                .map_err(|e| ParseError {
                     line: line_i,
-                    message: "arg was malformed".into(),
+                    message: format!("in \'{}\': {}", property_ident, e),
                 })?;
             x._human_string_set(bitstream, value)
-                .map_err(|_| ParseError {
+                .map_err(|e| ParseError {
                     line: line_i,
-                    message: "value was malformed".into(),
+                    message: format!("in \'{}\': {}", property_ident, e),
                 })?;
         } else {
             return Err(ParseError {
@@ -0,0 +1,343 @@
+//! Readers and writers for raw bit grids, without any knowledge of properties
+//!
+//! These work with any [BitArray], given its dimensions. Only plane 0 is accessed.
+
+use std::io::{self, BufRead, BufReader, Read};
+
+use bittwiddler_core::prelude::*;
+
+use crate::parser::{ParseError, TopError};
+
+/// Write a grid of `0`/`1` characters, one line per row
+///
+/// If `tile_size` is given, a space is inserted between tiles horizontally
+/// and an empty line is inserted between tiles vertically.
+pub fn write_raw<W: io::Write>(
+    mut w: W,
+    bitstream: &(impl BitArray + ?Sized),
+    width: usize,
+    height: usize,
+    tile_size: Option<(usize, usize)>,
+) -> io::Result<()> {
+    for y in 0..height {
+        if let Some((_, th)) = tile_size {
+            if y != 0 && th != 0 && y % th == 0 {
+                writeln!(w)?;
+            }
+        }
+        let mut line = String::with_capacity(width * 2);
+        for x in 0..width {
+            if let Some((tw, _)) = tile_size {
+                if x != 0 && tw != 0 && x % tw == 0 {
+                    line.push(' ');
+                }
+            }
+            line.push(if bitstream.get(Coordinate::new(x, y)) {
+                '1'
+            } else {
+                '0'
+            });
+        }
+        writeln!(w, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// Read a grid of `0`/`1` characters as written by [write_raw]
+///
+/// All whitespace and empty lines are ignored, so tile separators are optional.
+/// Exactly `height` rows of exactly `width` bits are required.
+/// Nothing is written to the bitstream unless the whole file is valid.
+pub fn read_raw<R: io::Read>(
+    r: R,
+    bitstream: &mut (impl BitArray + ?Sized),
+    width: usize,
+    height: usize,
+) -> Result<(), TopError> {
+    let r = BufReader::new(r);
+    let mut bits = Vec::with_capacity(width * height);
+    let mut y = 0;
+    let mut last_line_i = 0;
+
+    for (line_i, l) in r.lines().enumerate() {
+        let l = l?;
+        last_line_i = line_i;
+        let mut x = 0;
+        for ch in l.chars() {
+            let val = match ch {
+                '0' => false,
+                '1' => true,
+                _ if ch.is_whitespace() => continue,
+                _ => {
+                    return Err(ParseError {
+                        line: line_i,
+                        message: format!("invalid character '{}'", ch),
+                    }
+                    .into())
+                }
+            };
+            if x >= width || y >= height {
+                return Err(ParseError {
+                    line: line_i,
+                    message: "too many bits".into(),
+                }
+                .into());
+            }
+            bits.push(val);
+            x += 1;
+        }
+
+        if x == 0 {
+            continue;
+        }
+        if x != width {
+            return Err(ParseError {
+                line: line_i,
+                message: format!("expected {} bits but got {}", width, x),
+            }
+            .into());
+        }
+        y += 1;
+    }
+
+    if y != height {
+        return Err(ParseError {
+            line: last_line_i,
+            message: format!("expected {} rows but got {}", height, y),
+        }
+        .into());
+    }
+
+    set_grid(bitstream, width, &bits);
+    Ok(())
+}
+
+/// Copy bits read in row-major order into the bitstream
+fn set_grid(bitstream: &mut (impl BitArray + ?Sized), width: usize, bits: &[bool]) {
+    for (i, &val) in bits.iter().enumerate() {
+        bitstream.set(Coordinate::new(i % width, i / width), val);
+    }
+}
+
+/// Which variant of PBM to write
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum PbmFormat {
+    /// `P1`
+    Ascii,
+    /// `P4`
+    Binary,
+}
+
+/// Write a netpbm bitmap, where set bits are black
+pub fn write_pbm<W: io::Write>(
+    mut w: W,
+    bitstream: &(impl BitArray + ?Sized),
+    width: usize,
+    height: usize,
+    format: PbmFormat,
+) -> io::Result<()> {
+    match format {
+        PbmFormat::Ascii => {
+            writeln!(w, "P1\n{} {}", width, height)?;
+            write_raw(w, bitstream, width, height, None)
+        }
+        PbmFormat::Binary => {
+            writeln!(w, "P4\n{} {}", width, height)?;
+            let mut row = vec![0u8; width.div_ceil(8)];
+            for y in 0..height {
+                row.fill(0);
+                for x in 0..width {
+                    if bitstream.get(Coordinate::new(x, y)) {
+                        row[x / 8] |= 0x80 >> (x % 8);
+                    }
+                }
+                w.write_all(&row)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Reads one whitespace-separated header token, skipping comments
+fn pbm_header_token<R: BufRead>(r: &mut R, line_i: &mut usize) -> Result<String, TopError> {
+    let mut tok = String::new();
+    let mut in_comment = false;
+    loop {
+        let mut b = [0u8];
+        if r.read(&mut b)? == 0 {
+            break;
+        }
+        let ch = b[0] as char;
+        if ch == '\n' {
+            *line_i += 1;
+            in_comment = false;
+        }
+        if in_comment {
+            continue;
+        }
+        if ch == '#' {
+            in_comment = true;
+        } else if ch.is_ascii_whitespace() {
+            if !tok.is_empty() {
+                break;
+            }
+        } else {
+            tok.push(ch);
+        }
+    }
+    Ok(tok)
+}
+
+/// Read a netpbm bitmap in either `P1` or `P4` format, where black pixels are set bits
+///
+/// The dimensions in the file must match `width` and `height`.
+/// Nothing is written to the bitstream unless the whole file is valid.
+pub fn read_pbm<R: io::Read>(
+    r: R,
+    bitstream: &mut (impl BitArray + ?Sized),
+    width: usize,
+    height: usize,
+) -> Result<(), TopError> {
+    let mut r = BufReader::new(r);
+    let mut line_i = 0;
+
+    let magic = pbm_header_token(&mut r, &mut line_i)?;
+    let format = match magic.as_str() {
+        "P1" => PbmFormat::Ascii,
+        "P4" => PbmFormat::Binary,
+        _ => {
+            return Err(ParseError {
+                line: line_i,
+                message: "not a PBM file".into(),
+            }
+            .into())
+        }
+    };
+    let mut dims = [0; 2];
+    for dim in &mut dims {
+        let tok = pbm_header_token(&mut r, &mut line_i)?;
+        *dim = tok.parse::<usize>().map_err(|_| ParseError {
+            line: line_i,
+            message: format!("invalid dimension '{}'", tok),
+        })?;
+    }
+    if dims != [width, height] {
+        return Err(ParseError {
+            line: line_i,
+            message: format!(
+                "expected {}x{} but got {}x{}",
+                width, height, dims[0], dims[1]
+            ),
+        }
+        .into());
+    }
+
+    let mut bits = Vec::with_capacity(width * height);
+    match format {
+        PbmFormat::Ascii => {
+            // P1 doesn't require rows to be on separate lines, so only count digits
+            let mut data = String::new();
+            r.read_to_string(&mut data)?;
+            let mut pixels = data.chars().filter(|ch| !ch.is_whitespace());
+            for _ in 0..width * height {
+                let val = match pixels.next() {
+                    Some('0') => false,
+                    Some('1') => true,
+                    _ => {
+                        return Err(ParseError {
+                            line: line_i,
+                            message: "missing or invalid pixel data".into(),
+                        }
+                        .into())
+                    }
+                };
+                bits.push(val);
+            }
+        }
+        PbmFormat::Binary => {
+            let mut row = vec![0u8; width.div_ceil(8)];
+            for _ in 0..height {
+                r.read_exact(&mut row)?;
+                for x in 0..width {
+                    bits.push(row[x / 8] & (0x80 >> (x % 8)) != 0);
+                }
+            }
+        }
+    }
+
+    set_grid(bitstream, width, &bits);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_plane() -> BitPlane {
+        let mut p = BitPlane::new(10, 3);
+        p.set(Coordinate::new(0, 0), true);
+        p.set(Coordinate::new(9, 1), true);
+        p.set(Coordinate::new(4, 2), true);
+        p
+    }
+
+    #[test]
+    fn raw_round_trip() {
+        let p = test_plane();
+        let mut out = Vec::new();
+        write_raw(&mut out, &p, 10, 3, Some((4, 2))).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&out).unwrap(),
+            "1000 0000 00\n0000 0000 01\n\n0000 1000 00\n"
+        );
+
+        let mut p2 = BitPlane::new(10, 3);
+        read_raw(&out[..], &mut p2, 10, 3).unwrap();
+        assert_eq!(p2.words(), p.words());
+
+        assert!(read_raw(&out[..], &mut p2, 10, 4).is_err());
+        assert!(read_raw(&out[..], &mut p2, 9, 3).is_err());
+        assert!(read_raw("0000000002\n".as_bytes(), &mut p2, 10, 1).is_err());
+    }
+
+    #[test]
+    fn read_errors() {
+        let line = |e: TopError| match e {
+            TopError::ParseError(e) => e.line,
+            TopError::IoError(e) => panic!("{}", e),
+        };
+        let mut p = test_plane();
+
+        // the first rows are fine, but the bitstream isn't touched
+        let e = read_raw("1111\n1111\n\n".as_bytes(), &mut p, 4, 3).unwrap_err();
+        assert_eq!(line(e), 2);
+        let e = read_raw("1111\n11\n1111\n".as_bytes(), &mut p, 4, 3).unwrap_err();
+        assert_eq!(line(e), 1);
+        assert!(read_pbm("P1\n4 3\n1111 1111 111".as_bytes(), &mut p, 4, 3).is_err());
+        assert_eq!(p.words(), test_plane().words());
+    }
+
+    #[test]
+    fn pbm_round_trip() {
+        let p = test_plane();
+        for format in [PbmFormat::Ascii, PbmFormat::Binary] {
+            let mut out = Vec::new();
+            write_pbm(&mut out, &p, 10, 3, format).unwrap();
+
+            let mut p2 = BitPlane::new(10, 3);
+            read_pbm(&out[..], &mut p2, 10, 3).unwrap();
+            assert_eq!(p2.words(), p.words());
+            assert!(read_pbm(&out[..], &mut p2, 10, 2).is_err());
+        }
+
+        let mut p2 = BitPlane::new(10, 3);
+        read_pbm(
+            "P1\n# comment\n10 3\n1000000000 0000000001\n0000100000\n".as_bytes(),
+            &mut p2,
+            10,
+            3,
+        )
+        .unwrap();
+        assert_eq!(p2.words(), p.words());
+    }
+}
@@ -0,0 +1,115 @@
+//! Walks the entire hierarchy, producing the same human-readable names as the text file writer
+
+use bittwiddler_core::prelude::*;
+
+#[derive(Default)]
+struct StatePiecesHolder(Vec<(String, String)>);
+impl HumanSinkForStatePieces for StatePiecesHolder {
+    fn add_state_piece(&mut self, arg: &str, val: &str) {
+        self.0.push((arg.to_owned(), val.to_owned()));
+    }
+}
+
+pub(crate) fn format_sublevel_name(
+    prefix: &str,
+    sublevel_name: &str,
+    sublevel_obj: &(impl HumanLevelThatHasState + ?Sized),
+    write_dot: bool,
+) -> String {
+    let mut sublevel_full_name = prefix.to_string();
+    sublevel_full_name.push_str(sublevel_name);
+
+    let mut x = StatePiecesHolder::default();
+    sublevel_obj._human_dump_my_state(&mut x);
+
+    if !x.0.is_empty() {
+        sublevel_full_name.push('[');
+        for (i, xi) in x.0.iter().enumerate() {
+            if i != 0 {
+                sublevel_full_name.push_str(", ");
+            }
+            sublevel_full_name.push_str(&xi.0);
+            sublevel_full_name.push('=');
+            sublevel_full_name.push_str(&xi.1);
+        }
+        sublevel_full_name.push(']');
+    }
+
+    if write_dot {
+        sublevel_full_name.push('.');
+    }
+
+    sublevel_full_name
+}
+
+/// Builds the full human-readable name of a property from typed accessor objects
+///
+/// Each entry is the name of a sublevel or field along with the object holding its state,
+/// e.g. `[("tile", &tile), ("property_one", &tile.property_one())]`
+/// gives `tile[x=1, y=0].property_one`.
+pub fn accessor_path(segments: &[(&str, &dyn HumanLevelThatHasState)]) -> String {
+    let mut ret = String::new();
+    for (i, (name, obj)) in segments.iter().enumerate() {
+        ret = format_sublevel_name(&ret, name, *obj, i != segments.len() - 1);
+    }
+    ret
+}
+
+fn walk_recurse<E>(
+    level: &dyn HumanLevelDynamicAccessor,
+    prefix: &str,
+    keep: &mut impl FnMut(&dyn PropertyAccessorDyn) -> bool,
+    f: &mut impl FnMut(&str, &dyn PropertyAccessorDyn) -> Result<(), E>,
+) -> Result<(), E> {
+    for (sublevel_idx, sublevel_name) in level._human_sublevels().iter().enumerate() {
+        for sublevel_obj in level._human_construct_all_sublevels(sublevel_idx) {
+            let sublevel_full_name =
+                format_sublevel_name(prefix, sublevel_name, &*sublevel_obj, true);
+            walk_recurse(&*sublevel_obj, &sublevel_full_name, keep, f)?;
+        }
+    }
+
+    for (field_idx, field_name) in level._human_fields().iter().enumerate() {
+        for field_obj in level._human_construct_all_fields(field_idx) {
+            // names are only built for fields which are kept
+            if !keep(&*field_obj) {
+                continue;
+            }
+            let field_full_name = format_sublevel_name(prefix, field_name, &*field_obj, false);
+            f(&field_full_name, &*field_obj)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Like [try_for_each_field], but skips (without building their names) fields
+/// for which `keep` returns false
+pub(crate) fn try_for_each_kept_field<E>(
+    root: &dyn HumanLevelDynamicAccessor,
+    mut keep: impl FnMut(&dyn PropertyAccessorDyn) -> bool,
+    mut f: impl FnMut(&str, &dyn PropertyAccessorDyn) -> Result<(), E>,
+) -> Result<(), E> {
+    walk_recurse(root, "", &mut keep, &mut f)
+}
+
+/// Calls `f` with the full name (e.g. `tile[x=1, y=0].property_one`) of every property
+/// in the hierarchy, stopping at the first error
+pub fn try_for_each_field<E>(
+    root: &dyn HumanLevelDynamicAccessor,
+    f: impl FnMut(&str, &dyn PropertyAccessorDyn) -> Result<(), E>,
+) -> Result<(), E> {
+    try_for_each_kept_field(root, |_| true, f)
+}
+
+/// Calls `f` with the full name (e.g. `tile[x=1, y=0].property_one`) of every property
+/// in the hierarchy
+pub fn for_each_field(
+    root: &dyn HumanLevelDynamicAccessor,
+    mut f: impl FnMut(&str, &dyn PropertyAccessorDyn),
+) {
+    let _ = try_for_each_field::<()>(root, |name, field| {
+        f(name, field);
+        Ok(())
+    });
+}
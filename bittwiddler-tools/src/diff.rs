@@ -0,0 +1,126 @@
+//! Compares two bitstreams of the same device property-by-property
+
+use std::collections::BTreeSet;
+use std::fmt::Display;
+
+use bittwiddler_core::prelude::*;
+use bittwiddler_textfile::for_each_field;
+
+/// One property whose bits differ between the two bitstreams
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct PropertyChange {
+    pub path: String,
+    pub old: String,
+    pub new: String,
+}
+impl Display for PropertyChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} -> {}", self.path, self.old, self.new)
+    }
+}
+
+/// One bit which differs between the two bitstreams, but which isn't part of any property
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct UnclaimedBitChange {
+    pub coordinate: Coordinate,
+    pub old: bool,
+    pub new: bool,
+}
+impl Display for UnclaimedBitChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.coordinate, self.old as u8, self.new as u8
+        )
+    }
+}
+
+/// Every difference between two bitstreams
+///
+/// Properties are listed in hierarchy order, and unclaimed bits are listed in row-major order
+/// (plane by plane).
+#[derive(Clone, PartialEq, Eq, Debug, Default, Hash)]
+pub struct BitstreamDiff {
+    pub properties: Vec<PropertyChange>,
+    pub unclaimed_bits: Vec<UnclaimedBitChange>,
+}
+impl BitstreamDiff {
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty() && self.unclaimed_bits.is_empty()
+    }
+}
+impl Display for BitstreamDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for x in &self.properties {
+            writeln!(f, "{}", x)?;
+        }
+        for x in &self.unclaimed_bits {
+            writeln!(f, "{}", x)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare `old` and `new`, which must both be bitstreams for the device described by `hierarchy`
+///
+/// A property is reported as changed if any of its bits changed,
+/// even if this doesn't change its value.
+///
+/// Panics if the two bitstreams do not have the same planes and sizes.
+pub fn diff<A: BoundedBitArray, B: BoundedBitArray>(
+    old: &A,
+    new: &B,
+    hierarchy: &dyn HumanLevelDynamicAccessor,
+) -> BitstreamDiff {
+    assert_eq!(
+        old.num_planes(),
+        new.num_planes(),
+        "bitstreams must have the same planes"
+    );
+    for plane in 0..old.num_planes() {
+        assert_eq!(
+            (old.plane_width(plane), old.plane_height(plane)),
+            (new.plane_width(plane), new.plane_height(plane)),
+            "bitstreams must be the same size"
+        );
+    }
+
+    let mut ret = BitstreamDiff::default();
+    let mut claimed = BTreeSet::new();
+    for_each_field(hierarchy, |path, field| {
+        let mut changed = false;
+        for biti in 0..field._human_num_bits() {
+            for copyi in 0..field._human_num_bit_copies(biti) {
+                let (c, _) = field._human_get_bit_copy_pos(biti, copyi);
+                claimed.insert(c);
+                if old.get(c) != new.get(c) {
+                    changed = true;
+                }
+            }
+        }
+        if changed {
+            ret.properties.push(PropertyChange {
+                path: path.to_owned(),
+                old: field._human_string_get(old).into_owned(),
+                new: field._human_string_get(new).into_owned(),
+            });
+        }
+    });
+
+    for c in old.all_coordinates() {
+        if claimed.contains(&c) {
+            continue;
+        }
+        let (old, new) = (old.get(c), new.get(c));
+        if old != new {
+            ret.unclaimed_bits.push(UnclaimedBitChange {
+                coordinate: c,
+                old,
+                new,
+            });
+        }
+    }
+
+    ret
+}
@@ -0,0 +1,218 @@
+//! Design-rule checks across properties
+//!
+//! Many combinations of individually valid property values are illegal on real hardware
+//! (e.g. two drivers on one wire). Rules are written against accessors, and a [Drc]
+//! runs all of them over a bitstream so that a bad configuration can be rejected
+//! before it is ever written out.
+
+use std::error::Error;
+use std::fmt::Display;
+
+use bittwiddler_core::prelude::*;
+use bittwiddler_textfile::for_each_field;
+
+/// How bad a violation is
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub enum Severity {
+    /// Suspicious, but not known to be harmful
+    Warning,
+    /// Must never be programmed into a device
+    Error,
+}
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// One broken rule
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct Violation {
+    /// Name of the rule which was broken
+    pub rule: String,
+    pub severity: Severity,
+    /// Human-readable name of the offending property, e.g. `tile[x=1, y=0].property_one`
+    pub path: String,
+    pub message: String,
+}
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}: {} [{}]",
+            self.severity, self.path, self.message, self.rule
+        )
+    }
+}
+
+/// Collects the violations found by a single rule
+pub struct RuleOutput<'a> {
+    rule: &'a str,
+    violations: &'a mut Vec<Violation>,
+}
+impl RuleOutput<'_> {
+    pub fn report(
+        &mut self,
+        severity: Severity,
+        path: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.violations.push(Violation {
+            rule: self.rule.to_owned(),
+            severity,
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+    pub fn error(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.report(Severity::Error, path, message);
+    }
+    pub fn warning(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.report(Severity::Warning, path, message);
+    }
+}
+
+/// A design rule which can be checked against a bitstream of type `B`
+///
+/// Paths for typed accessors can be built with [bittwiddler_textfile::accessor_path].
+pub trait Rule<B: ?Sized> {
+    fn name(&self) -> &str;
+    fn check(&self, bitstream: &B, out: &mut RuleOutput);
+}
+
+/// A [Rule] made out of a closure, see [rule]
+pub struct FnRule<F> {
+    name: String,
+    f: F,
+}
+impl<B: ?Sized, F: Fn(&B, &mut RuleOutput)> Rule<B> for FnRule<F> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn check(&self, bitstream: &B, out: &mut RuleOutput) {
+        (self.f)(bitstream, out)
+    }
+}
+/// Make a rule out of a closure which is given the whole bitstream
+pub fn rule<B: ?Sized, F: Fn(&B, &mut RuleOutput)>(name: impl Into<String>, f: F) -> FnRule<F> {
+    FnRule {
+        name: name.into(),
+        f,
+    }
+}
+
+/// A [Rule] which is evaluated once for every property in the hierarchy, see [field_rule]
+pub struct FieldRule<F> {
+    name: String,
+    f: F,
+}
+impl<B: BitArray + HumanLevelDynamicAccessor, F> Rule<B> for FieldRule<F>
+where
+    F: Fn(&str, &dyn PropertyAccessorDyn, &B, &mut RuleOutput),
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn check(&self, bitstream: &B, out: &mut RuleOutput) {
+        for_each_field(bitstream, |path, field| {
+            (self.f)(path, field, bitstream, out)
+        });
+    }
+}
+/// Make a rule out of a closure which is given the full name and accessor of every property
+pub fn field_rule<F>(name: impl Into<String>, f: F) -> FieldRule<F> {
+    FieldRule {
+        name: name.into(),
+        f,
+    }
+}
+
+/// Every violation found by a [Drc] run, in the order the rules were added
+#[derive(Clone, PartialEq, Eq, Debug, Default, Hash)]
+pub struct DrcReport {
+    pub violations: Vec<Violation>,
+}
+impl DrcReport {
+    pub fn is_clean(&self) -> bool {
+        self.violations.is_empty()
+    }
+    pub fn has_errors(&self) -> bool {
+        self.violations
+            .iter()
+            .any(|v| v.severity == Severity::Error)
+    }
+    pub fn errors(&self) -> impl Iterator<Item = &Violation> {
+        self.violations
+            .iter()
+            .filter(|v| v.severity == Severity::Error)
+    }
+    pub fn warnings(&self) -> impl Iterator<Item = &Violation> {
+        self.violations
+            .iter()
+            .filter(|v| v.severity == Severity::Warning)
+    }
+
+    /// Fails if any violation is an error, otherwise returns the (possibly empty) report of warnings
+    pub fn into_result(self) -> Result<Self, Self> {
+        if self.has_errors() {
+            Err(self)
+        } else {
+            Ok(self)
+        }
+    }
+}
+impl Display for DrcReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for v in &self.violations {
+            writeln!(f, "{}", v)?;
+        }
+        Ok(())
+    }
+}
+impl Error for DrcReport {}
+
+/// A collection of rules for bitstreams of type `B`
+pub struct Drc<'a, B: ?Sized> {
+    rules: Vec<Box<dyn Rule<B> + 'a>>,
+}
+impl<B: ?Sized> Default for Drc<'_, B> {
+    fn default() -> Self {
+        Self { rules: Vec::new() }
+    }
+}
+impl<'a, B: ?Sized> Drc<'a, B> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_rule(&mut self, rule: impl Rule<B> + 'a) {
+        self.rules.push(Box::new(rule));
+    }
+    pub fn with_rule(mut self, rule: impl Rule<B> + 'a) -> Self {
+        self.add_rule(rule);
+        self
+    }
+
+    /// Run every rule and collect all violations
+    pub fn run(&self, bitstream: &B) -> DrcReport {
+        let mut violations = Vec::new();
+        for rule in &self.rules {
+            let mut out = RuleOutput {
+                rule: rule.name(),
+                violations: &mut violations,
+            };
+            rule.check(bitstream, &mut out);
+        }
+        DrcReport { violations }
+    }
+
+    /// Run every rule, failing if any error-level violation is found
+    ///
+    /// Intended to be called right before writing out a bitstream.
+    pub fn check(&self, bitstream: &B) -> Result<DrcReport, DrcReport> {
+        self.run(bitstream).into_result()
+    }
+}
@@ -0,0 +1,207 @@
+//! Aggregates how often each bit flips across a corpus of bitstreams (e.g. fuzzer output)
+
+use std::{error::Error, fmt::Display, io};
+
+use bittwiddler_core::prelude::*;
+
+use crate::render::{Image, Rgb};
+
+/// Per-coordinate counts of how many variants differ from a base bitstream
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Heatmap {
+    plane: usize,
+    width: usize,
+    height: usize,
+    counts: Vec<usize>,
+    num_variants: usize,
+}
+impl Heatmap {
+    /// Create an empty heatmap for a `width` x `height` area of `plane`
+    pub fn new(width: usize, height: usize, plane: usize) -> Self {
+        Self {
+            plane,
+            width,
+            height,
+            counts: vec![0; width * height],
+            num_variants: 0,
+        }
+    }
+
+    /// Create a heatmap covering all of `plane` of `base` and add every variant
+    pub fn from_variants<'v, V: BitArray + 'v>(
+        base: &impl BoundedBitArray,
+        plane: usize,
+        variants: impl IntoIterator<Item = &'v V>,
+    ) -> Self {
+        let mut ret = Self::new(base.plane_width(plane), base.plane_height(plane), plane);
+        for variant in variants {
+            ret.add_variant(base, variant);
+        }
+        ret
+    }
+
+    /// Count every bit where `variant` differs from `base`
+    pub fn add_variant(
+        &mut self,
+        base: &(impl BitArray + ?Sized),
+        variant: &(impl BitArray + ?Sized),
+    ) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let c = Coordinate::new_in_plane(x, y, self.plane);
+                if base.get(c) != variant.get(c) {
+                    self.counts[y * self.width + x] += 1;
+                }
+            }
+        }
+        self.num_variants += 1;
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    pub fn num_variants(&self) -> usize {
+        self.num_variants
+    }
+
+    /// How many variants differed from the base at `(x, y)`
+    pub fn count(&self, x: usize, y: usize) -> usize {
+        self.counts[y * self.width + x]
+    }
+
+    pub fn max_count(&self) -> usize {
+        self.counts.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Sum the counts of every tile into one `tile_w` x `tile_h` grid, indexed `[y][x]`
+    ///
+    /// Partial tiles at the right and bottom edges are included.
+    pub fn fold_tiles(
+        &self,
+        tile_w: usize,
+        tile_h: usize,
+    ) -> Result<Vec<Vec<usize>>, EmptyTileError> {
+        if tile_w == 0 || tile_h == 0 {
+            return Err(EmptyTileError { tile_w, tile_h });
+        }
+        let mut ret = vec![vec![0; tile_w]; tile_h];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                ret[y % tile_h][x % tile_w] += self.count(x, y);
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Write the tile-folded counts as CSV with one row per tile-local position
+    ///
+    /// Returns an [io::ErrorKind::InvalidInput] error if the tile size is 0.
+    pub fn write_tile_csv<W: io::Write>(
+        &self,
+        mut w: W,
+        tile_w: usize,
+        tile_h: usize,
+    ) -> io::Result<()> {
+        let folded = self
+            .fold_tiles(tile_w, tile_h)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        writeln!(w, "x,y,flips")?;
+        for (y, row) in folded.iter().enumerate() {
+            for (x, count) in row.iter().enumerate() {
+                writeln!(w, "{},{},{}", x, y, count)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Render as an image going from black (never flipped)
+    /// through red and yellow to white (flipped the most)
+    pub fn to_image(&self, scale: usize) -> Image {
+        let scale = scale.max(1);
+        let max = self.max_count();
+        let mut img = Image::new(self.width * scale, self.height * scale);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let color = heat_color(self.count(x, y), max);
+                for py in 0..scale {
+                    for px in 0..scale {
+                        img.set(x * scale + px, y * scale + py, color);
+                    }
+                }
+            }
+        }
+        img
+    }
+}
+
+/// A tile size passed to [Heatmap::fold_tiles] was 0
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct EmptyTileError {
+    pub tile_w: usize,
+    pub tile_h: usize,
+}
+impl Display for EmptyTileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "tile size {}x{} is empty", self.tile_w, self.tile_h)
+    }
+}
+impl Error for EmptyTileError {}
+
+fn heat_color(count: usize, max: usize) -> Rgb {
+    if max == 0 {
+        return [0, 0, 0];
+    }
+    let level = (count * 765 / max) as u32;
+    let r = level.min(255) as u8;
+    let g = level.saturating_sub(255).min(255) as u8;
+    let b = level.saturating_sub(510).min(255) as u8;
+    [r, g, b]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heatmap() {
+        let base = BitPlane::new(4, 4);
+        let mut variants = vec![BitPlane::new(4, 4), BitPlane::new(4, 4)];
+        variants[0].set(Coordinate::new(1, 0), true);
+        variants[0].set(Coordinate::new(3, 3), true);
+        variants[1].set(Coordinate::new(1, 0), true);
+
+        let h = Heatmap::from_variants(&base, 0, &variants);
+        assert_eq!(h.num_variants(), 2);
+        assert_eq!(h.count(1, 0), 2);
+        assert_eq!(h.count(3, 3), 1);
+        assert_eq!(h.max_count(), 2);
+
+        assert_eq!(h.fold_tiles(2, 2).unwrap(), [[0, 2], [0, 1]]);
+        let mut csv = Vec::new();
+        h.write_tile_csv(&mut csv, 2, 2).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&csv).unwrap(),
+            "x,y,flips\n0,0,0\n1,0,2\n0,1,0\n1,1,1\n"
+        );
+
+        assert_eq!(
+            h.fold_tiles(0, 2),
+            Err(EmptyTileError {
+                tile_w: 0,
+                tile_h: 2
+            })
+        );
+        let mut csv = Vec::new();
+        let err = h.write_tile_csv(&mut csv, 2, 0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(csv.is_empty());
+
+        let img = h.to_image(1);
+        assert_eq!(img.get(1, 0), [255, 255, 255]);
+        assert_eq!(img.get(0, 0), [0, 0, 0]);
+        assert_eq!(img.get(3, 3), [255, 127, 0]);
+    }
+}
@@ -0,0 +1,9 @@
+//! This crate contains tools for checking and analyzing bitstreams,
+//! built on top of the dynamic hierarchy generated by the macros
+
+pub mod diff;
+pub mod drc;
+pub mod heatmap;
+pub mod lookup;
+pub mod overlap;
+pub mod render;
@@ -0,0 +1,73 @@
+//! Reverse lookup from a coordinate in the bitstream to the property which owns it
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use bittwiddler_core::prelude::*;
+use bittwiddler_textfile::for_each_field;
+
+/// One bit of a property, identified by its full human-readable name
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct PropertyBit {
+    pub path: String,
+    pub biti: usize,
+    /// Which copy of a replicated bit this is, 0 for bits which are only stored once
+    pub copyi: usize,
+    pub invert: bool,
+}
+impl Display for PropertyBit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} bit {}", self.path, self.biti)?;
+        if self.copyi != 0 {
+            write!(f, " (copy {})", self.copyi)?;
+        }
+        if self.invert {
+            write!(f, " (inverted)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Index from every coordinate to the property bits stored there
+///
+/// This walks the entire hierarchy once when it is built.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct BitOwnerIndex {
+    owners: BTreeMap<Coordinate, Vec<PropertyBit>>,
+}
+impl BitOwnerIndex {
+    pub fn new(root: &dyn HumanLevelDynamicAccessor) -> Self {
+        let mut owners: BTreeMap<Coordinate, Vec<PropertyBit>> = BTreeMap::new();
+        for_each_field(root, |path, field| {
+            for biti in 0..field._human_num_bits() {
+                for copyi in 0..field._human_num_bit_copies(biti) {
+                    let (c, invert) = field._human_get_bit_copy_pos(biti, copyi);
+                    owners.entry(c).or_default().push(PropertyBit {
+                        path: path.to_owned(),
+                        biti,
+                        copyi,
+                        invert,
+                    });
+                }
+            }
+        });
+        Self { owners }
+    }
+
+    /// Every property bit stored at `c`
+    ///
+    /// This is normally either empty or a single bit, unless properties overlap.
+    pub fn owners(&self, c: Coordinate) -> &[PropertyBit] {
+        self.owners.get(&c).map_or(&[], |x| x)
+    }
+
+    /// The first property bit stored at `c`
+    pub fn owner(&self, c: Coordinate) -> Option<&PropertyBit> {
+        self.owners(c).first()
+    }
+
+    /// Every coordinate which is owned by at least one property, in sorted order
+    pub fn iter(&self) -> impl Iterator<Item = (Coordinate, &[PropertyBit])> {
+        self.owners.iter().map(|(c, bits)| (*c, &bits[..]))
+    }
+}
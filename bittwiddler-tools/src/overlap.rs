@@ -0,0 +1,48 @@
+//! Checks that no two property bits are stored at the same coordinate
+//!
+//! If two accessors return the same coordinate, writing to one property will
+//! silently corrupt the other one.
+
+use std::fmt::Display;
+
+use bittwiddler_core::prelude::*;
+
+use crate::lookup::{BitOwnerIndex, PropertyBit};
+
+/// Two property bits which are stored at the same coordinate
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct Overlap {
+    pub coordinate: Coordinate,
+    pub first: PropertyBit,
+    pub second: PropertyBit,
+}
+impl Display for Overlap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} and {} are both at {}",
+            self.first, self.second, self.coordinate
+        )
+    }
+}
+
+/// Walk the entire hierarchy and return every pair of bits which overlap
+///
+/// If more than two bits share a coordinate, every possible pair is reported.
+/// The result is sorted by coordinate.
+pub fn find_overlaps(root: &dyn HumanLevelDynamicAccessor) -> Vec<Overlap> {
+    let index = BitOwnerIndex::new(root);
+    let mut ret = Vec::new();
+    for (c, bits) in index.iter() {
+        for i in 0..bits.len() {
+            for j in (i + 1)..bits.len() {
+                ret.push(Overlap {
+                    coordinate: c,
+                    first: bits[i].clone(),
+                    second: bits[j].clone(),
+                });
+            }
+        }
+    }
+    ret
+}
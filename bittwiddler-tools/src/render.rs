@@ -0,0 +1,271 @@
+//! Renders bitstreams to images, which is often the first step of reverse engineering
+
+use std::collections::HashMap;
+use std::io;
+
+use bittwiddler_core::prelude::*;
+
+use crate::lookup::BitOwnerIndex;
+
+/// An 8-bit-per-channel RGB color
+pub type Rgb = [u8; 3];
+
+const SET_UNOWNED: Rgb = [255, 255, 255];
+const CLEAR_UNOWNED: Rgb = [0, 0, 0];
+const GRID: Rgb = [96, 96, 96];
+const CHANGED_SET: Rgb = [255, 0, 0];
+const CHANGED_CLEARED: Rgb = [0, 128, 255];
+
+/// Where per-property colors come from
+#[derive(Default)]
+pub enum PropertyColors<'a> {
+    /// Every bit is black or white
+    #[default]
+    None,
+    /// Each property in the hierarchy gets its own color
+    ///
+    /// All instances of the same property (e.g. `property_one` in every tile) get the same color.
+    Hierarchy(&'a BitOwnerIndex),
+    /// Arbitrary function, e.g. using the `owner` table emitted for each spreadsheet tile
+    Function(&'a dyn Fn(Coordinate) -> Option<Rgb>),
+}
+
+/// Options for [render]
+pub struct RenderSettings<'a> {
+    /// Which plane of the bitstream to render
+    pub plane: usize,
+    /// Each bit is drawn as a square of this many pixels
+    pub scale: usize,
+    /// If set, draw gridlines between tiles of this width and height (e.g. a tile's `W` and `H`)
+    pub tile_size: Option<(usize, usize)>,
+    pub colors: PropertyColors<'a>,
+    /// If set, bits which differ from this bitstream are highlighted
+    /// (red if they became set, blue if they became clear)
+    pub reference: Option<&'a dyn BitArray>,
+}
+impl<'a> Default for RenderSettings<'a> {
+    fn default() -> Self {
+        Self {
+            plane: 0,
+            scale: 4,
+            tile_size: None,
+            colors: PropertyColors::None,
+            reference: None,
+        }
+    }
+}
+
+/// A simple RGB image in row-major order
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Rgb>,
+}
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[0; 3]; width * height],
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+    pub fn set(&mut self, x: usize, y: usize, color: Rgb) {
+        self.pixels[y * self.width + x] = color;
+    }
+
+    /// Write as a binary PPM (P6) file
+    pub fn write_ppm<W: io::Write>(&self, mut w: W) -> io::Result<()> {
+        write!(w, "P6\n{} {}\n255\n", self.width, self.height)?;
+        w.write_all(self.pixels.as_flattened())
+    }
+
+    /// Write as a PNG file
+    pub fn write_png<W: io::Write>(&self, w: W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(w, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(self.pixels.as_flattened())?;
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+/// Strips state from a full property name, e.g. `tile[x=1, y=0].property_one` -> `tile.property_one`
+fn property_kind(path: &str) -> String {
+    let mut ret = String::new();
+    let mut depth = 0;
+    for ch in path.chars() {
+        match ch {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            _ if depth == 0 => ret.push(ch),
+            _ => {}
+        }
+    }
+    ret
+}
+
+/// 64-bit FNV-1a, which (unlike the standard library's hasher) is the same across Rust versions
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut h: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        h ^= *b as u64;
+        h = h.wrapping_mul(0x100000001b3);
+    }
+    h
+}
+
+/// Pick a bright, deterministic color for a name
+///
+/// The color only depends on the name, so it stays the same between runs and builds.
+fn color_for_name(name: &str) -> Rgb {
+    let h = fnv1a(name.as_bytes());
+    // spread the hue over 6 segments of a color wheel at full saturation and value
+    let hue = (h % 1536) as u32;
+    let seg = hue / 256;
+    let f = (hue % 256) as u8;
+    match seg {
+        0 => [255, f, 0],
+        1 => [255 - f, 255, 0],
+        2 => [0, 255, f],
+        3 => [0, 255 - f, 255],
+        4 => [f, 0, 255],
+        _ => [255, 0, 255 - f],
+    }
+}
+
+fn dim(c: Rgb) -> Rgb {
+    c.map(|x| x / 4)
+}
+
+/// Render one plane of `bitstream` to an image
+pub fn render(bitstream: &impl BoundedBitArray, settings: &RenderSettings) -> Image {
+    let plane = settings.plane;
+    let scale = settings.scale.max(1);
+    let w = bitstream.plane_width(plane);
+    let h = bitstream.plane_height(plane);
+    let mut img = Image::new(w * scale, h * scale);
+
+    let mut kind_colors: HashMap<String, Rgb> = HashMap::new();
+
+    for y in 0..h {
+        for x in 0..w {
+            let c = Coordinate::new_in_plane(x, y, plane);
+            if !bitstream.in_bounds(c) {
+                continue;
+            }
+            let val = bitstream.get(c);
+
+            let prop_color = match &settings.colors {
+                PropertyColors::None => None,
+                PropertyColors::Hierarchy(index) => index.owner(c).map(|owner| {
+                    *kind_colors
+                        .entry(property_kind(&owner.path))
+                        .or_insert_with_key(|k| color_for_name(k))
+                }),
+                PropertyColors::Function(f) => f(c),
+            };
+            let mut color = match (prop_color, val) {
+                (Some(color), true) => color,
+                (Some(color), false) => dim(color),
+                (None, true) => SET_UNOWNED,
+                (None, false) => CLEAR_UNOWNED,
+            };
+            if let Some(reference) = settings.reference {
+                if reference.get(c) != val {
+                    color = if val { CHANGED_SET } else { CHANGED_CLEARED };
+                }
+            }
+
+            for py in 0..scale {
+                for px in 0..scale {
+                    let on_grid = settings.tile_size.is_some_and(|(tw, th)| {
+                        (px == 0 && tw != 0 && x % tw == 0) || (py == 0 && th != 0 && y % th == 0)
+                    });
+                    img.set(
+                        x * scale + px,
+                        y * scale + py,
+                        if on_grid && scale > 1 { GRID } else { color },
+                    );
+                }
+            }
+        }
+    }
+
+    img
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_property_kind() {
+        assert_eq!(
+            property_kind("tile[x=1, y=0].property_two[n=3]"),
+            "tile.property_two"
+        );
+        assert_eq!(
+            color_for_name("tile.property_two"),
+            color_for_name("tile.property_two")
+        );
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        // reference values, so that colors can't silently change
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn test_render() {
+        let mut p = BitPlane::new(4, 2);
+        let reference = BitPlane::new(4, 2);
+        p.set(Coordinate::new(3, 1), true);
+        p.set(Coordinate::new(1, 0), true);
+
+        let img = render(
+            &p,
+            &RenderSettings {
+                scale: 1,
+                ..Default::default()
+            },
+        );
+        assert_eq!((img.width, img.height), (4, 2));
+        assert_eq!(img.get(3, 1), SET_UNOWNED);
+        assert_eq!(img.get(2, 1), CLEAR_UNOWNED);
+
+        let colors = |c: Coordinate| (c.x == 1).then_some([0, 200, 0]);
+        let img = render(
+            &p,
+            &RenderSettings {
+                scale: 2,
+                tile_size: Some((2, 2)),
+                colors: PropertyColors::Function(&colors),
+                reference: Some(&reference),
+                ..Default::default()
+            },
+        );
+        assert_eq!((img.width, img.height), (8, 4));
+        assert_eq!(img.get(0, 0), GRID);
+        assert_eq!(img.get(4, 3), GRID);
+        assert_eq!(img.get(3, 3), [0, 50, 0]);
+        assert_eq!(img.get(7, 3), CHANGED_SET);
+
+        let mut ppm = Vec::new();
+        img.write_ppm(&mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n8 4\n255\n"));
+        assert_eq!(ppm.len(), 11 + 8 * 4 * 3);
+        let mut png = Vec::new();
+        img.write_png(&mut png).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+    }
+}